@@ -1,63 +1,235 @@
+pub mod counted_files;
+pub mod git_files;
+pub mod ignore_rules;
+pub mod path_patterns;
+pub mod walk_errors;
 pub mod walk_path_result;
+pub mod walk_tree;
 
-use crate::line_counter::{count_lines, detect_encoding};
+use crate::language::detection::detect_language;
+use crate::language::Language;
+use crate::line_counter::{count_lines, detect_encoding, EmbeddedCount, LineCount};
 use crate::result_printer::{PrinterEntry, ResultPrinter};
 use crate::summarizer::Summarizer;
+use counted_files::{CountedFiles, FileId};
 use encoding_rs::Encoding;
+use ignore_rules::IgnoreRules;
+use path_patterns::{relative_path, PathPatterns};
+use rayon::prelude::*;
 use std::collections::HashSet;
-use std::io::Error;
-use std::path::{Component, Path};
+use std::ffi::OsString;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use walk_errors::WalkErrors;
 use walk_path_result::WalkPathResult;
-use walkdir::WalkDir;
+use walk_tree::{Ancestor, WalkEntry, WalkFolder};
 
+/// Number of files counted together on the thread pool before they are reported, which
+/// bounds the results held in memory for a large folder, and how much is counted past an
+/// unreadable file with `--strict`
+const COUNT_BATCH_SIZE: usize = 256;
+
+/// Decides which entries get counted. An entry is skipped when it is ignored by an ignore
+/// file, named in `exclude`, or matched by an exclude pattern. Files must then have one of
+/// the `include_extensions` and match an include pattern, when either is given.
 pub struct ExcludeOptions<'a> {
-    pub include_extensions: &'a HashSet<String>,
-    pub exclude: &'a HashSet<String>,
+    /// Root being walked, which patterns are relative to
+    pub root: &'a Path,
+    /// Extensions and names are compared on their raw bytes
+    pub include_extensions: &'a HashSet<OsString>,
+    pub exclude: &'a HashSet<OsString>,
+    pub patterns: &'a PathPatterns,
+    /// Ignore file rules, `None` when they are disabled with `--no-ignore`
+    pub ignore: Option<&'a IgnoreRules>,
+    /// Walk into symlinked folders and count symlinked files, instead of skipping them
+    pub follow_symlinks: bool,
+    /// Skip folders on another file system than their parent
+    pub one_file_system: bool,
+    /// Files to count, relative to `root`, instead of reading folders from the disk.
+    /// Set to the files tracked by git with `--git-tracked`.
+    pub listed_files: Option<&'a [PathBuf]>,
+}
+
+impl ExcludeOptions<'_> {
+    /// Whether the entry, and everything below it, is left out of the walk
+    fn is_pruned(&self, path: &Path, is_dir: bool) -> bool {
+        self.ignore
+            .is_some_and(|ignore| ignore.is_ignored(path, is_dir))
+            || (!self.patterns.is_empty()
+                && self.patterns.is_excluded(&relative_path(self.root, path)))
+    }
+
+    /// Whether a file found without walking, such as in a git tree, would be counted by a walk
+    /// of `root`, checking every folder above it as the walk would
+    pub fn is_counted(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(self.root) else {
+            return false;
+        };
+        let mut entry = self.root.to_path_buf();
+        let mut components = relative.components().peekable();
+        while let Some(component) = components.next() {
+            entry.push(component);
+            let is_dir = components.peek().is_some();
+            if self.is_pruned(&entry, is_dir) || !self.is_selected(&entry, !is_dir) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether an entry which is not pruned passes the name, extension and include filters
+    fn is_selected(&self, path: &Path, is_file: bool) -> bool {
+        let name_allowed = self.exclude.is_empty()
+            || !path
+                .file_name()
+                .is_some_and(|name| self.exclude.contains(name));
+        let extension_allowed = self.include_extensions.is_empty()
+            || path
+                .extension()
+                .is_none_or(|extension| self.include_extensions.contains(extension));
+        name_allowed
+            && extension_allowed
+            && (!is_file || self.patterns.is_included(&relative_path(self.root, path)))
+    }
+}
+
+/// The outcome of counting a single file, computed off the printing thread
+pub struct FileCount {
+    encoding: &'static Encoding,
+    confidence: f32,
+    language: Option<&'static Language>,
+    /// `None` when the file could not be decoded
+    lines: Option<(LineCount, Vec<EmbeddedCount>)>,
+}
+
+/// Detects the encoding and language of a file and counts its lines. Files which cannot be
+/// decoded are still counted as invalid, other errors mean the file could not be read.
+pub fn count_file(encoding: Option<&'static Encoding>, path: &Path) -> Result<FileCount, Error> {
+    let mut confidence = -1f32;
+    let used_encoding: &'static Encoding = match encoding {
+        None => {
+            let detected_encoding = detect_encoding(path)?;
+            confidence = detected_encoding.confidence;
+            detected_encoding.encoding
+        }
+        Some(e) => e,
+    };
+    let language = detect_language(path);
+    let lines = match count_lines(path, used_encoding, language) {
+        Ok(lines) => Some(lines),
+        Err(err) if err.kind() == ErrorKind::InvalidData => None,
+        Err(err) => return Err(err),
+    };
+    Ok(FileCount {
+        encoding: used_encoding,
+        confidence,
+        language,
+        lines,
+    })
 }
 
+/// Counts a single file given directly as an argument, unless it was already counted
+#[allow(clippy::too_many_arguments)]
 pub fn handle_file_entry(
     encoding: Option<&'static Encoding>,
     entry_path: &Path,
-    entry: &PrinterEntry,
     depth: i32,
     walk_result: &mut WalkPathResult,
     printer: &(impl ResultPrinter + ?Sized),
     summarizer: &mut (impl Summarizer + ?Sized),
+    walk_errors: &mut WalkErrors,
+    counted: &mut CountedFiles,
 ) -> Result<(), Error> {
-    let mut confidence = -1f32;
-    let used_encoding: &'static Encoding = match encoding {
-        None => {
-            let detected = detect_encoding(entry_path);
-            if detected.is_err() {
-                return Err(detected.err().unwrap());
-            }
-            let detected_encoding = detected?;
-            confidence = detected_encoding.confidence;
-            detected_encoding.encoding
+    if !counted.insert(FileId::from_path(entry_path)) {
+        return Ok(());
+    }
+    let entry = PrinterEntry::from_path(entry_path);
+    match count_file(encoding, entry_path) {
+        Ok(count) => {
+            report_file(
+                count,
+                entry_path,
+                &entry,
+                depth,
+                walk_result,
+                printer,
+                summarizer,
+            );
+            Ok(())
         }
-        Some(e) => e,
-    };
-    match count_lines(entry_path, used_encoding) {
-        Ok(lines) => {
+        Err(error) => report_unreadable(error, &entry, depth, walk_result, printer, walk_errors),
+    }
+}
+
+fn report_file(
+    count: FileCount,
+    entry_path: &Path,
+    entry: &PrinterEntry,
+    depth: i32,
+    walk_result: &mut WalkPathResult,
+    printer: &(impl ResultPrinter + ?Sized),
+    summarizer: &mut (impl Summarizer + ?Sized),
+) {
+    let FileCount {
+        encoding,
+        confidence,
+        language,
+        lines,
+    } = count;
+    match lines {
+        Some((lines, embedded)) => {
             walk_result.line_count += lines;
             if lines.lines == 0 {
-                printer.print_empty_file(entry, -1, used_encoding, depth, confidence);
+                printer.print_empty_file(entry, -1, encoding, language, depth, confidence);
                 walk_result.empty_file_count += 1;
             } else {
-                printer.print_file(entry, lines, -1, used_encoding, depth, confidence);
+                printer.print_file(entry, lines, -1, encoding, language, depth, confidence);
                 walk_result.file_count += 1;
             };
-            summarizer.append_entry(entry_path, lines);
-            Ok(())
+            summarizer.append_entry(entry_path, lines, language, &embedded);
         }
-        Err(_) => {
-            printer.print_error_file(entry, -1, used_encoding, depth, confidence);
+        None => {
+            printer.print_error_file(entry, -1, encoding, language, depth, confidence);
             walk_result.error_file_count += 1;
-            Ok(())
         }
     }
 }
 
+fn report_unreadable(
+    error: Error,
+    entry: &PrinterEntry,
+    depth: i32,
+    walk_result: &mut WalkPathResult,
+    printer: &(impl ResultPrinter + ?Sized),
+    walk_errors: &mut WalkErrors,
+) -> Result<(), Error> {
+    let error = walk_errors.record(entry.path, error)?;
+    printer.print_walk_error(entry, error, depth);
+    walk_result.unreadable_count += 1;
+    Ok(())
+}
+
+fn report_unreadable_root(
+    path: &Path,
+    error: Error,
+    printer: &(impl ResultPrinter + ?Sized),
+    walk_errors: &mut WalkErrors,
+) -> Result<WalkPathResult, Error> {
+    let mut walk_result = WalkPathResult::new();
+    printer.print_header(path, 0);
+    report_unreadable(
+        error,
+        &PrinterEntry::from_path(path),
+        0,
+        &mut walk_result,
+        printer,
+        walk_errors,
+    )?;
+    Ok(walk_result)
+}
+
+/// Counts the folder and reports it as a tree, folder by folder
+#[allow(clippy::too_many_arguments)]
 pub fn walk_path(
     path: &Path,
     encoding: Option<&'static Encoding>,
@@ -65,151 +237,296 @@ pub fn walk_path(
     printer: &(impl ResultPrinter + ?Sized),
     summarizer: &mut (impl Summarizer + ?Sized),
     exclude_options: &ExcludeOptions,
+    walk_errors: &mut WalkErrors,
+    counted: &mut CountedFiles,
 ) -> Result<WalkPathResult, Error> {
-    let mut walk_result = WalkPathResult::new();
-    walk_result.folder_count = 1;
-    let skip_name_check = exclude_options.exclude.is_empty();
-    let skip_ext_check = exclude_options.include_extensions.is_empty();
-
-    let entries = WalkDir::new(path)
-        .min_depth(1)
-        .max_depth(1)
-        .into_iter()
-        .count();
-
-    if depth == 0 {
-        printer.print_header(path, entries);
-    } else {
-        printer.print_folder(&PrinterEntry::from_path(path), entries, depth - 1);
-    }
-
-    for entry in WalkDir::new(path).min_depth(1).max_depth(1) {
-        match entry {
-            Ok(dir_entry) => {
-                let entry_path = dir_entry.path();
-                let file_ext = entry_path.extension();
-                let entry = PrinterEntry {
-                    name: entry_path
-                        .file_name()
-                        .unwrap()
-                        .to_os_string()
-                        .into_string()
-                        .unwrap(),
-                    path: entry_path,
-                };
-
-                if (skip_name_check || !exclude_options.exclude.contains(&*entry.name))
-                    && (file_ext.is_none()
-                        || skip_ext_check
-                        || exclude_options
-                            .include_extensions
-                            .contains(&*file_ext.unwrap().to_os_string().into_string().unwrap()))
-                {
-                    if dir_entry.file_type().is_file() {
-                        handle_file_entry(
-                            encoding,
-                            entry_path,
-                            &entry,
-                            depth,
-                            &mut walk_result,
-                            printer,
-                            summarizer,
-                        )?
-                    } else {
-                        match walk_path(
-                            entry_path,
-                            encoding,
-                            depth + 1,
-                            printer,
-                            summarizer,
-                            exclude_options,
-                        ) {
-                            Ok(sub_res) => {
-                                printer.print_folder_total(sub_res.line_count, depth + 1);
-                                walk_result += sub_res;
-                            }
-                            Err(err) => return Err(err),
-                        }
-                    }
-                }
-            }
-            Err(err) => return Err(Error::from(err)),
-        }
+    let folder = match WalkFolder::read_root(path.to_path_buf(), exclude_options) {
+        Ok(folder) => folder,
+        Err(error) => return report_unreadable_root(path, error, printer, walk_errors),
+    };
+    if !counted.insert(folder.id) {
+        return Ok(report_counted_root(path, printer));
     }
-
-    Ok(walk_result)
+    FolderReporter {
+        encoding,
+        exclude_options,
+        printer,
+        summarizer,
+        walk_errors,
+        counted,
+        flat: false,
+    }
+    .report_folder(folder, depth, None)
 }
 
+/// Counts the folder and reports every file in it as a flat list
 pub fn simple_walk_path(
     path: &Path,
     encoding: Option<&'static Encoding>,
     printer: &(impl ResultPrinter + ?Sized),
     summarizer: &mut (impl Summarizer + ?Sized),
     exclude_options: &ExcludeOptions,
+    walk_errors: &mut WalkErrors,
+    counted: &mut CountedFiles,
 ) -> Result<WalkPathResult, Error> {
-    let mut walk_result = WalkPathResult::new();
-    walk_result.folder_count = 1;
-    let skip_name_check = exclude_options.exclude.is_empty();
-    let skip_ext_check = exclude_options.include_extensions.is_empty();
-
-    let entries = WalkDir::new(path)
-        .min_depth(1)
-        .max_depth(1)
-        .into_iter()
-        .count();
-
-    printer.print_header(path, entries);
-
-    for entry in WalkDir::new(path).min_depth(1) {
-        match entry {
-            Ok(dir_entry) => {
-                let entry_path = dir_entry.path();
-                let file_ext = entry_path.extension();
-                let entry = PrinterEntry {
-                    name: entry_path
-                        .file_name()
-                        .unwrap()
-                        .to_os_string()
-                        .into_string()
-                        .unwrap(),
-                    path: entry_path,
-                };
-                let mut is_excluded = false;
-                let components: Vec<Component> = entry_path.components().collect();
-                for i in 0..dir_entry.depth() {
-                    if let Component::Normal(name) = components[components.len() - i - 1] {
-                        if exclude_options.exclude.contains(name.to_str().unwrap()) {
-                            is_excluded = true;
-                            break;
+    let folder = match WalkFolder::read_root(path.to_path_buf(), exclude_options) {
+        Ok(folder) => folder,
+        Err(error) => return report_unreadable_root(path, error, printer, walk_errors),
+    };
+    if !counted.insert(folder.id) {
+        return Ok(report_counted_root(path, printer));
+    }
+    FolderReporter {
+        encoding,
+        exclude_options,
+        printer,
+        summarizer,
+        walk_errors,
+        counted,
+        flat: true,
+    }
+    .report_folder(folder, 0, None)
+}
+
+/// Reports a root which was already counted through another path argument as empty
+fn report_counted_root(path: &Path, printer: &(impl ResultPrinter + ?Sized)) -> WalkPathResult {
+    printer.print_header(path, 0);
+    WalkPathResult::new()
+}
+
+/// Walks a folder depth first, counting the files of each folder on the rayon thread pool
+/// and reporting them as soon as they are counted, so that only the folders being walked
+/// are held in memory
+struct FolderReporter<'a, P: ResultPrinter + ?Sized, S: Summarizer + ?Sized> {
+    encoding: Option<&'static Encoding>,
+    exclude_options: &'a ExcludeOptions<'a>,
+    printer: &'a P,
+    summarizer: &'a mut S,
+    walk_errors: &'a mut WalkErrors,
+    counted: &'a mut CountedFiles,
+    /// Report every file as a flat list rather than as a tree
+    flat: bool,
+}
+
+impl<P: ResultPrinter + ?Sized, S: Summarizer + ?Sized> FolderReporter<'_, P, S> {
+    fn report_folder(
+        &mut self,
+        folder: WalkFolder,
+        depth: i32,
+        parent: Option<&Ancestor>,
+    ) -> Result<WalkPathResult, Error> {
+        let mut walk_result = WalkPathResult::new();
+        walk_result.folder_count = 1;
+
+        if depth == 0 {
+            self.printer.print_header(&folder.path, folder.entries);
+        } else if !self.flat {
+            self.printer.print_folder(
+                &PrinterEntry::from_path(&folder.path),
+                folder.entries,
+                depth - 1,
+            );
+        }
+        let file_depth = if self.flat { -1 } else { depth };
+
+        let ancestor = Ancestor {
+            path: &folder.path,
+            id: folder.id,
+            parent,
+        };
+        let mut children = folder.children.into_iter().peekable();
+        while children.peek().is_some() {
+            // Files up to the next folder are counted together, so that entries are still
+            // reported, and checked for duplicates, in walk order
+            let mut files = vec![];
+            while let Some(WalkEntry::File { path, id }) =
+                children.next_if(|child| matches!(child, WalkEntry::File { .. }))
+            {
+                if self.counted.insert(id) {
+                    files.push(path);
+                }
+            }
+            self.report_files(files, file_depth, &mut walk_result)?;
+
+            match children.next() {
+                Some(WalkEntry::Folder { path, id, listed }) => {
+                    if let Some(looped) = ancestor.find(id) {
+                        let error = Error::other(format!(
+                            "File system loop found: {} points to an ancestor {}",
+                            path.display(),
+                            looped.display()
+                        ));
+                        self.report_unreadable(&path, error, file_depth, &mut walk_result)?;
+                    } else if self.counted.insert(id) {
+                        match WalkFolder::read(path.clone(), id, listed, self.exclude_options) {
+                            Ok(sub_folder) => {
+                                let sub_res =
+                                    self.report_folder(sub_folder, depth + 1, Some(&ancestor))?;
+                                if !self.flat {
+                                    self.printer
+                                        .print_folder_total(sub_res.line_count, depth + 1);
+                                }
+                                walk_result += sub_res;
+                            }
+                            Err(error) => {
+                                self.report_unreadable(&path, error, file_depth, &mut walk_result)?
+                            }
                         }
                     }
                 }
+                Some(WalkEntry::Unreadable { path, error }) => {
+                    self.report_unreadable(&path, error, file_depth, &mut walk_result)?
+                }
+                Some(WalkEntry::File { .. }) | None => {}
+            }
+        }
 
-                if (skip_name_check || !is_excluded)
-                    && (file_ext.is_none()
-                        || skip_ext_check
-                        || exclude_options
-                            .include_extensions
-                            .contains(&*file_ext.unwrap().to_os_string().into_string().unwrap()))
-                {
-                    if dir_entry.file_type().is_file() {
-                        handle_file_entry(
-                            encoding,
-                            entry_path,
-                            &entry,
-                            -1,
-                            &mut walk_result,
-                            printer,
-                            summarizer,
-                        )?
-                    } else {
-                        walk_result.folder_count += 1;
-                    }
+        Ok(walk_result)
+    }
+
+    /// Counts the files in parallel, a batch at a time, reporting each batch in order before
+    /// counting the next one
+    fn report_files(
+        &mut self,
+        files: Vec<PathBuf>,
+        depth: i32,
+        walk_result: &mut WalkPathResult,
+    ) -> Result<(), Error> {
+        let encoding = self.encoding;
+        for batch in files.chunks(COUNT_BATCH_SIZE) {
+            let counts: Vec<Result<FileCount, Error>> = batch
+                .par_iter()
+                .map(|path| count_file(encoding, path))
+                .collect();
+            for (path, count) in batch.iter().zip(counts) {
+                match count {
+                    Ok(count) => report_file(
+                        count,
+                        path,
+                        &PrinterEntry::from_path(path),
+                        depth,
+                        walk_result,
+                        self.printer,
+                        self.summarizer,
+                    ),
+                    Err(error) => self.report_unreadable(path, error, depth, walk_result)?,
                 }
             }
-            Err(err) => return Err(Error::from(err)),
         }
+        Ok(())
     }
 
-    Ok(walk_result)
+    fn report_unreadable(
+        &mut self,
+        path: &Path,
+        error: Error,
+        depth: i32,
+        walk_result: &mut WalkPathResult,
+    ) -> Result<(), Error> {
+        report_unreadable(
+            error,
+            &PrinterEntry::from_path(path),
+            depth,
+            walk_result,
+            self.printer,
+            self.walk_errors,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result_printer::noop_result_printer::NoopResultPrinter;
+    use serde_json::Value;
+    use std::{env, fs};
+
+    /// Keeps the path of every counted file
+    struct CountedPaths(Vec<PathBuf>);
+
+    impl Summarizer for CountedPaths {
+        fn append_entry(
+            &mut self,
+            file_path: &Path,
+            _entry: LineCount,
+            _language: Option<&'static Language>,
+            _embedded: &[EmbeddedCount],
+        ) {
+            self.0.push(file_path.to_path_buf());
+        }
+
+        fn set_limit(&mut self, _limit: u32) {}
+
+        fn print_summary(&mut self, _total: LineCount) {}
+
+        fn summary_json(&mut self, _total: LineCount) -> Value {
+            Value::Null
+        }
+    }
+
+    /// Walks each root in turn, like path arguments, returning the counted files
+    fn walk(roots: &[PathBuf]) -> Vec<PathBuf> {
+        let (extensions, exclude) = (HashSet::new(), HashSet::new());
+        let patterns = PathPatterns::new(&[], &[], &[], &[]).unwrap();
+        let mut summarizer = CountedPaths(vec![]);
+        let mut walk_errors = WalkErrors::new(true);
+        let mut counted = CountedFiles::new();
+        for root in roots {
+            let exclude_options = ExcludeOptions {
+                root,
+                include_extensions: &extensions,
+                exclude: &exclude,
+                patterns: &patterns,
+                ignore: None,
+                follow_symlinks: false,
+                one_file_system: false,
+                listed_files: None,
+            };
+            walk_path(
+                root,
+                None,
+                0,
+                &NoopResultPrinter {},
+                &mut summarizer,
+                &exclude_options,
+                &mut walk_errors,
+                &mut counted,
+            )
+            .unwrap();
+        }
+        summarizer.0
+    }
+
+    fn temp_folder(name: &str) -> PathBuf {
+        let root = env::temp_dir().join(format!("lnav-{name}-{}", std::process::id()));
+        fs::create_dir_all(root.join("src/core")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("src/core/lib.rs"), "// lib\n").unwrap();
+        fs::canonicalize(root).unwrap()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn counts_hard_linked_files_once() {
+        let root = temp_folder("hard-link");
+        fs::hard_link(root.join("src/main.rs"), root.join("src/core/main.rs")).unwrap();
+        let counted = walk(std::slice::from_ref(&root));
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            counted,
+            [root.join("src/core/lib.rs"), root.join("src/core/main.rs")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn counts_overlapping_roots_once() {
+        let root = temp_folder("overlapping-roots");
+        let counted = walk(&[root.join("src"), root.join("src/core"), root.clone()]);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            counted,
+            [root.join("src/core/lib.rs"), root.join("src/main.rs")]
+        );
+    }
 }
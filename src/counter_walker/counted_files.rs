@@ -0,0 +1,62 @@
+use std::collections::HashSet;
+use std::fs::Metadata;
+use std::path::Path;
+
+/// Device and inode of a file or folder, the same whichever path, hard link or symlink
+/// it is reached through
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FileId {
+    device: u64,
+    inode: u64,
+}
+
+impl FileId {
+    /// `None` on platforms without device and inode numbers
+    #[cfg(unix)]
+    pub fn from_metadata(metadata: &Metadata) -> Option<FileId> {
+        use std::os::unix::fs::MetadataExt;
+        Some(FileId {
+            device: metadata.dev(),
+            inode: metadata.ino(),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn from_metadata(_metadata: &Metadata) -> Option<FileId> {
+        None
+    }
+
+    /// Whether both entries are on the same device, assumed when either is unknown
+    pub fn same_device(a: Option<FileId>, b: Option<FileId>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => a.device == b.device,
+            _ => true,
+        }
+    }
+
+    /// Identifies the file or folder the path points to, following symlinks
+    pub fn from_path(path: &Path) -> Option<FileId> {
+        path.metadata()
+            .ok()
+            .and_then(|metadata| FileId::from_metadata(&metadata))
+    }
+}
+
+/// Files and folders already counted across every path argument
+pub struct CountedFiles {
+    ids: HashSet<FileId>,
+}
+
+impl CountedFiles {
+    pub fn new() -> CountedFiles {
+        CountedFiles {
+            ids: HashSet::new(),
+        }
+    }
+
+    /// Marks the entry as counted, returning false when it already was. Entries which
+    /// cannot be identified are always counted.
+    pub fn insert(&mut self, id: Option<FileId>) -> bool {
+        id.is_none_or(|id| self.ids.insert(id))
+    }
+}
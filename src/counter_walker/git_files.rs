@@ -0,0 +1,98 @@
+use git2::{Repository, StatusOptions};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Lists the files below `root` which are in the index of the git repository containing it,
+/// relative to `root` and sorted. With `include_untracked`, untracked files which are not
+/// ignored by git are listed too.
+pub fn git_files(root: &Path, include_untracked: bool) -> Result<Vec<PathBuf>, git2::Error> {
+    let repository = Repository::discover(root)?;
+    let workdir = repository
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("the repository has no working tree"))?;
+    let workdir =
+        fs::canonicalize(workdir).map_err(|err| git2::Error::from_str(&err.to_string()))?;
+
+    let mut paths: Vec<PathBuf> = repository
+        .index()?
+        .iter()
+        .map(|entry| bytes_to_path(&entry.path))
+        .collect();
+    if include_untracked {
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_ignored(false);
+        for status in repository.statuses(Some(&mut options))?.iter() {
+            if status.status().is_wt_new() {
+                paths.push(bytes_to_path(status.path_bytes()));
+            }
+        }
+    }
+
+    let mut files: Vec<PathBuf> = paths
+        .into_iter()
+        .filter_map(|path| {
+            workdir
+                .join(path)
+                .strip_prefix(root)
+                .ok()
+                .map(Path::to_path_buf)
+        })
+        .collect();
+    // Files with merge conflicts are in the index once per side
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Paths in the index are raw bytes, which git only guarantees to be UTF-8 on Windows
+#[cfg(unix)]
+pub fn bytes_to_path(bytes: &[u8]) -> PathBuf {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+pub fn bytes_to_path(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn lists_ignored_files_added_to_the_index() {
+        let root = env::temp_dir().join(format!("lnav-git-files-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let root = fs::canonicalize(&root).unwrap();
+        let repository = Repository::init(&root).unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        for file in ["main.rs", "keep.log", "skip.log", "notes.txt"] {
+            fs::write(root.join(file), "line\n").unwrap();
+        }
+        let mut index = repository.index().unwrap();
+        for file in [".gitignore", "main.rs", "keep.log"] {
+            index.add_path(Path::new(file)).unwrap();
+        }
+        index.write().unwrap();
+
+        let tracked = git_files(&root, false);
+        let untracked = git_files(&root, true);
+        fs::remove_dir_all(&root).unwrap();
+
+        let paths = |files: &[&str]| files.iter().map(PathBuf::from).collect::<Vec<_>>();
+        assert_eq!(
+            tracked.unwrap(),
+            paths(&[".gitignore", "keep.log", "main.rs"])
+        );
+        assert_eq!(
+            untracked.unwrap(),
+            paths(&[".gitignore", "keep.log", "main.rs", "notes.txt"])
+        );
+    }
+}
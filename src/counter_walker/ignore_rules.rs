@@ -0,0 +1,95 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// Per-directory ignore files, highest precedence first
+const IGNORE_FILES: &[&str] = &[".lnavignore", ".ignore", ".gitignore"];
+
+/// Decides which entries of a walk are ignored by `.gitignore`, `.ignore` and `.lnavignore`
+/// files, the repository's `.git/info/exclude` and the user's global excludes file.
+/// Rules in deeper directories take precedence over those in their parents.
+pub struct IgnoreRules {
+    /// Directory above which no ignore files are read, the repository root when there is one
+    top: PathBuf,
+    /// Matchers that apply to the whole walk, highest precedence first
+    global: Vec<Gitignore>,
+    directories: RwLock<HashMap<PathBuf, Arc<Vec<Gitignore>>>>,
+}
+
+impl IgnoreRules {
+    /// Prepares the rules for walking `root`, which is expected to be canonical
+    pub fn new(root: &Path) -> IgnoreRules {
+        let repository = root
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .map(Path::to_path_buf);
+        let mut global = vec![];
+        if let Some(repository) = &repository {
+            let mut builder = GitignoreBuilder::new(repository);
+            builder.add(repository.join(".git").join("info").join("exclude"));
+            if let Ok(exclude) = builder.build() {
+                global.push(exclude);
+            }
+        }
+        global.push(Gitignore::global().0);
+
+        IgnoreRules {
+            top: repository.unwrap_or_else(|| root.to_path_buf()),
+            global,
+            directories: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Whether the entry is ignored. Directories are not walked into once ignored, so a
+    /// file inside an ignored directory cannot be re-included, matching git.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if is_dir && path.file_name().is_some_and(|name| name == ".git") {
+            return true;
+        }
+        for dir in path.ancestors().skip(1) {
+            for matcher in self.matchers(dir).iter() {
+                match matcher.matched(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            if dir == self.top {
+                break;
+            }
+        }
+        for matcher in &self.global {
+            match matcher.matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+
+    fn matchers(&self, dir: &Path) -> Arc<Vec<Gitignore>> {
+        if let Some(matchers) = self.directories.read().unwrap().get(dir) {
+            return matchers.clone();
+        }
+        let matchers: Arc<Vec<Gitignore>> = Arc::new(
+            IGNORE_FILES
+                .iter()
+                .map(|name| dir.join(name))
+                .filter(|file| file.is_file())
+                .filter_map(|file| {
+                    let mut builder = GitignoreBuilder::new(dir);
+                    builder.add(file);
+                    builder.build().ok()
+                })
+                .collect(),
+        );
+        self.directories
+            .write()
+            .unwrap()
+            .insert(dir.to_path_buf(), matchers.clone());
+        matchers
+    }
+}
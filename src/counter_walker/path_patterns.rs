@@ -0,0 +1,91 @@
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::bytes::RegexSet;
+use std::path::{Component, Path, PathBuf};
+
+/// Glob and regex patterns matched against the raw bytes of paths relative to the root
+/// being walked, always using `/` as the separator
+pub struct PathPatterns {
+    include_globs: GlobSet,
+    exclude_globs: GlobSet,
+    include_regexes: RegexSet,
+    exclude_regexes: RegexSet,
+}
+
+impl PathPatterns {
+    /// Globs without a `/` match the name of an entry at any depth, like in `.gitignore` files
+    pub fn new(
+        include_globs: &[String],
+        exclude_globs: &[String],
+        include_regexes: &[String],
+        exclude_regexes: &[String],
+    ) -> Result<PathPatterns, String> {
+        Ok(PathPatterns {
+            include_globs: build_glob_set(include_globs)?,
+            exclude_globs: build_glob_set(exclude_globs)?,
+            include_regexes: RegexSet::new(include_regexes).map_err(|err| err.to_string())?,
+            exclude_regexes: RegexSet::new(exclude_regexes).map_err(|err| err.to_string())?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include_globs.is_empty()
+            && self.exclude_globs.is_empty()
+            && self.include_regexes.is_empty()
+            && self.exclude_regexes.is_empty()
+    }
+
+    /// Whether the file or directory matches an exclude pattern
+    pub fn is_excluded(&self, relative_path: &Path) -> bool {
+        self.exclude_globs.is_match(relative_path)
+            || (!self.exclude_regexes.is_empty()
+                && self
+                    .exclude_regexes
+                    .is_match(&slash_separated(relative_path)))
+    }
+
+    /// Whether the file matches an include pattern, or there are none
+    pub fn is_included(&self, relative_path: &Path) -> bool {
+        (self.include_globs.is_empty() && self.include_regexes.is_empty())
+            || self.include_globs.is_match(relative_path)
+            || (!self.include_regexes.is_empty()
+                && self
+                    .include_regexes
+                    .is_match(&slash_separated(relative_path)))
+    }
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for original in patterns {
+        let pattern = original.trim_start_matches("./").trim_end_matches('/');
+        let pattern = if pattern.contains('/') {
+            pattern.trim_start_matches('/').to_string()
+        } else {
+            format!("**/{pattern}")
+        };
+        let glob = GlobBuilder::new(&pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|err| format!("error parsing glob '{original}': {}", err.kind()))?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|err| err.to_string())
+}
+
+/// The path of an entry relative to the walked root
+pub fn relative_path(root: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .collect()
+}
+
+/// The raw bytes of a relative path, joined with `/` on every platform
+fn slash_separated(relative_path: &Path) -> Vec<u8> {
+    relative_path
+        .components()
+        .map(|component| component.as_os_str().as_encoded_bytes())
+        .collect::<Vec<_>>()
+        .join(&b'/')
+}
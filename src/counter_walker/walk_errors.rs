@@ -0,0 +1,39 @@
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+/// An entry which could not be read during the walk
+pub struct WalkError {
+    pub path: PathBuf,
+    pub error: Error,
+}
+
+/// Collects the entries which could not be read, or stops the walk at the first one
+/// when `strict` is set
+pub struct WalkErrors {
+    strict: bool,
+    pub errors: Vec<WalkError>,
+}
+
+impl WalkErrors {
+    pub fn new(strict: bool) -> WalkErrors {
+        WalkErrors {
+            strict,
+            errors: vec![],
+        }
+    }
+
+    /// Records the error, or returns it annotated with the path in strict mode
+    pub fn record(&mut self, path: &Path, error: Error) -> Result<&Error, Error> {
+        if self.strict {
+            return Err(Error::new(
+                error.kind(),
+                format!("{}: {error}", path.display()),
+            ));
+        }
+        self.errors.push(WalkError {
+            path: path.to_path_buf(),
+            error,
+        });
+        Ok(&self.errors.last().unwrap().error)
+    }
+}
@@ -8,6 +8,8 @@ pub struct WalkPathResult {
     pub file_count: usize,
     pub empty_file_count: usize,
     pub error_file_count: usize,
+    /// Entries which could not be read, such as folders without permission
+    pub unreadable_count: usize,
 }
 
 #[allow(dead_code)]
@@ -17,7 +19,7 @@ impl WalkPathResult {
     }
 
     pub fn total_entries(&self) -> usize {
-        self.total_files() + self.folder_count
+        self.total_files() + self.folder_count + self.unreadable_count
     }
 
     pub fn new() -> WalkPathResult {
@@ -27,6 +29,7 @@ impl WalkPathResult {
             file_count: 0,
             empty_file_count: 0,
             error_file_count: 0,
+            unreadable_count: 0,
         }
     }
 }
@@ -41,6 +44,7 @@ impl Add for WalkPathResult {
             file_count: self.file_count + rhs.file_count,
             empty_file_count: self.empty_file_count + rhs.empty_file_count,
             error_file_count: self.error_file_count + rhs.error_file_count,
+            unreadable_count: self.unreadable_count + rhs.unreadable_count,
         }
     }
 }
@@ -52,5 +56,6 @@ impl AddAssign for WalkPathResult {
         self.file_count += rhs.file_count;
         self.empty_file_count += rhs.empty_file_count;
         self.error_file_count += rhs.error_file_count;
+        self.unreadable_count += rhs.unreadable_count;
     }
 }
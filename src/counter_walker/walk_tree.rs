@@ -0,0 +1,244 @@
+use crate::counter_walker::counted_files::FileId;
+use crate::counter_walker::ExcludeOptions;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+/// An entry of a folder, found but not counted yet
+pub enum WalkEntry<'a> {
+    File {
+        path: PathBuf,
+        id: Option<FileId>,
+    },
+    Folder {
+        path: PathBuf,
+        id: Option<FileId>,
+        /// Files listed below the folder, when the folder is not read from the disk
+        listed: Option<Vec<&'a Path>>,
+    },
+    Unreadable {
+        path: PathBuf,
+        error: Error,
+    },
+}
+
+/// A folder with its selected entries sorted by name. Only the folder itself is read:
+/// sub folders are read when the walk reaches them, so that results are reported as
+/// each folder is counted.
+pub struct WalkFolder<'a> {
+    pub path: PathBuf,
+    pub id: Option<FileId>,
+    /// Entries in the folder which are not ignored, including those filtered out by name
+    /// or extension and those which could not be read
+    pub entries: usize,
+    pub children: Vec<WalkEntry<'a>>,
+}
+
+impl<'a> WalkFolder<'a> {
+    /// Reads the folder a walk starts from, from the disk or from the files listed in the
+    /// exclude options. Entries which cannot be read become [WalkEntry::Unreadable] rather
+    /// than failing the walk, only an error reading the folder itself is returned.
+    pub fn read_root(
+        path: PathBuf,
+        exclude_options: &ExcludeOptions<'a>,
+    ) -> Result<WalkFolder<'a>, Error> {
+        let id = FileId::from_path(&path);
+        let listed = exclude_options
+            .listed_files
+            .map(|files| files.iter().map(PathBuf::as_path).collect());
+        Self::read(path, id, listed, exclude_options)
+    }
+
+    /// Reads a folder found by the walk, see [WalkFolder::read_root]
+    pub fn read(
+        path: PathBuf,
+        id: Option<FileId>,
+        listed: Option<Vec<&'a Path>>,
+        exclude_options: &ExcludeOptions,
+    ) -> Result<WalkFolder<'a>, Error> {
+        match listed {
+            Some(files) => Ok(Self::from_files(path, id, files, exclude_options)),
+            None => Self::from_disk(path, id, exclude_options),
+        }
+    }
+
+    fn from_disk(
+        path: PathBuf,
+        id: Option<FileId>,
+        exclude_options: &ExcludeOptions,
+    ) -> Result<WalkFolder<'a>, Error> {
+        let mut entries = 0;
+        let mut children = vec![];
+        for entry in WalkDir::new(&path)
+            .min_depth(1)
+            .max_depth(1)
+            .follow_links(exclude_options.follow_symlinks)
+            .sort_by_file_name()
+        {
+            let dir_entry = match entry {
+                Ok(dir_entry) => dir_entry,
+                Err(err) if err.depth() == 0 => return Err(walk_error(err)),
+                Err(err) => {
+                    entries += 1;
+                    children.push(WalkEntry::Unreadable {
+                        path: err.path().unwrap_or(&path).to_path_buf(),
+                        error: walk_error(err),
+                    });
+                    continue;
+                }
+            };
+            let file_type = dir_entry.file_type();
+            if exclude_options.is_pruned(dir_entry.path(), file_type.is_dir()) {
+                continue;
+            }
+            entries += 1;
+            if !exclude_options.is_selected(dir_entry.path(), file_type.is_file()) {
+                continue;
+            }
+            let entry_id = dir_entry
+                .metadata()
+                .ok()
+                .and_then(|metadata| FileId::from_metadata(&metadata));
+            let entry_path = dir_entry.into_path();
+            if file_type.is_file() {
+                children.push(WalkEntry::File {
+                    path: entry_path,
+                    id: entry_id,
+                });
+            } else if file_type.is_dir() {
+                if exclude_options.one_file_system && !FileId::same_device(entry_id, id) {
+                    continue;
+                }
+                children.push(WalkEntry::Folder {
+                    path: entry_path,
+                    id: entry_id,
+                    listed: None,
+                });
+            }
+        }
+
+        Ok(WalkFolder {
+            path,
+            id,
+            entries,
+            children,
+        })
+    }
+
+    /// Builds the folder from files listed below it, such as those tracked by git, instead of
+    /// reading it from the disk. Only folders with a listed file below them are included, and
+    /// listed files which no longer exist are left out.
+    fn from_files(
+        path: PathBuf,
+        id: Option<FileId>,
+        files: Vec<&'a Path>,
+        exclude_options: &ExcludeOptions,
+    ) -> WalkFolder<'a> {
+        let mut listed: BTreeMap<&OsStr, Listed> = BTreeMap::new();
+        for file in files {
+            let mut components = file.components();
+            let Some(Component::Normal(name)) = components.next() else {
+                continue;
+            };
+            let below = components.as_path();
+            if below.as_os_str().is_empty() {
+                listed.insert(name, Listed::File);
+            } else if let Listed::Folder(files) =
+                listed.entry(name).or_insert_with(|| Listed::Folder(vec![]))
+            {
+                files.push(below);
+            }
+        }
+
+        let mut entries = 0;
+        let mut children = vec![];
+        for (name, listed) in listed {
+            let child = path.join(name);
+            let is_dir = matches!(listed, Listed::Folder(_));
+            if exclude_options.is_pruned(&child, is_dir) {
+                continue;
+            }
+            entries += 1;
+            if !exclude_options.is_selected(&child, !is_dir) {
+                continue;
+            }
+            match listed {
+                Listed::File => children.extend(listed_file(child, exclude_options)),
+                Listed::Folder(files) => children.push(WalkEntry::Folder {
+                    id: FileId::from_path(&child),
+                    path: child,
+                    listed: Some(files),
+                }),
+            }
+        }
+
+        WalkFolder {
+            path,
+            id,
+            entries,
+            children,
+        }
+    }
+}
+
+/// An entry of a folder built from a list of files, with the files listed below a folder
+enum Listed<'a> {
+    File,
+    Folder(Vec<&'a Path>),
+}
+
+/// A listed file to count, unless it was deleted or is a symlink which is not followed
+fn listed_file<'a>(path: PathBuf, exclude_options: &ExcludeOptions) -> Option<WalkEntry<'a>> {
+    let metadata = match fs::symlink_metadata(&path) {
+        Ok(metadata) if metadata.is_symlink() => {
+            if !exclude_options.follow_symlinks {
+                return None;
+            }
+            fs::metadata(&path)
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => return None,
+        result => result,
+    };
+    let metadata = match metadata {
+        Ok(metadata) => metadata,
+        Err(error) => return Some(WalkEntry::Unreadable { path, error }),
+    };
+    metadata.is_file().then(|| WalkEntry::File {
+        path,
+        id: FileId::from_metadata(&metadata),
+    })
+}
+
+/// A folder being walked, linked to its parent to detect symlink loops
+pub struct Ancestor<'a> {
+    pub path: &'a Path,
+    pub id: Option<FileId>,
+    pub parent: Option<&'a Ancestor<'a>>,
+}
+
+impl Ancestor<'_> {
+    /// The path of the ancestor which is the same folder as `id`, if any
+    pub fn find(&self, id: Option<FileId>) -> Option<&Path> {
+        let id = id?;
+        let mut ancestor = Some(self);
+        while let Some(current) = ancestor {
+            if current.id == Some(id) {
+                return Some(current.path);
+            }
+            ancestor = current.parent;
+        }
+        None
+    }
+}
+
+/// The underlying error, without walkdir repeating the path it is reported with
+fn walk_error(err: walkdir::Error) -> Error {
+    if err.io_error().is_some() {
+        err.into_io_error().unwrap()
+    } else {
+        Error::from(err)
+    }
+}
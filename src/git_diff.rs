@@ -0,0 +1,214 @@
+pub mod diff_report;
+
+use crate::language::detection::detect_content_language;
+use crate::language::Language;
+use crate::line_counter::{classify_lines, LineCount};
+use encoding_rs::Encoding;
+use git2::{
+    Blob, Delta, DiffDelta, DiffFile, DiffFindOptions, DiffOptions, FileMode, Patch, Repository,
+};
+use serde_json::{json, Value};
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
+
+/// Lines added and removed by a change, classified like the lines of counted files
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DiffCount {
+    pub added: LineCount,
+    pub removed: LineCount,
+}
+
+impl AddAssign for DiffCount {
+    fn add_assign(&mut self, rhs: DiffCount) {
+        self.added += rhs.added;
+        self.removed += rhs.removed;
+    }
+}
+
+impl DiffCount {
+    /// Lines, code, comment and blank lines added minus those removed
+    pub fn net(&self) -> [i64; 4] {
+        let net = |added: u64, removed: u64| added as i64 - removed as i64;
+        [
+            net(self.added.lines, self.removed.lines),
+            net(self.added.code_lines(), self.removed.code_lines()),
+            net(self.added.comment_lines, self.removed.comment_lines),
+            net(self.added.blank_lines, self.removed.blank_lines),
+        ]
+    }
+
+    pub fn as_json(&self) -> Value {
+        let [lines, code_lines, comment_lines, blank_lines] = self.net();
+        json!({
+            "added": self.added.as_json(),
+            "removed": self.removed.as_json(),
+            "net": {
+                "lines": lines,
+                "code_lines": code_lines,
+                "comment_lines": comment_lines,
+                "blank_lines": blank_lines,
+            },
+        })
+    }
+}
+
+/// A file changed between two revisions
+pub struct FileDiff {
+    /// Path relative to the repository root, the old path for deleted files
+    pub path: PathBuf,
+    /// Path before the file was renamed or copied
+    pub old_path: Option<PathBuf>,
+    pub status: &'static str,
+    pub language: Option<&'static Language>,
+    /// `None` for binary files and files which cannot be decoded
+    pub count: Option<DiffCount>,
+}
+
+/// A revision being compared, as given and as resolved
+pub struct Revision {
+    pub name: String,
+    pub commit: String,
+}
+
+/// The files changed between two revisions of a repository
+pub struct RevisionDiff {
+    pub from: Revision,
+    pub to: Revision,
+    pub files: Vec<FileDiff>,
+}
+
+/// Counts the lines added and removed in every file changed between the `from` and `to`
+/// commits, for the files `is_counted` accepts given their path relative to the repository
+/// root. Renames are detected so that a moved file only counts its edits. Everything is
+/// read from the repository's object database, the working tree is not used.
+pub fn diff_revisions(
+    repository: &Repository,
+    from: &str,
+    to: &str,
+    encoding: Option<&'static Encoding>,
+    is_counted: impl Fn(&Path) -> bool,
+) -> Result<RevisionDiff, git2::Error> {
+    let from_commit = repository.revparse_single(from)?.peel_to_commit()?;
+    let to_commit = repository.revparse_single(to)?.peel_to_commit()?;
+
+    let mut options = DiffOptions::new();
+    options.context_lines(0).ignore_submodules(true);
+    let mut diff = repository.diff_tree_to_tree(
+        Some(&from_commit.tree()?),
+        Some(&to_commit.tree()?),
+        Some(&mut options),
+    )?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+    let mut files = vec![];
+    for (index, delta) in diff.deltas().enumerate() {
+        let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+            continue;
+        };
+        if delta.new_file().mode() == FileMode::Commit || !is_counted(path) {
+            continue;
+        }
+        let old_blob = find_blob(repository, &delta.old_file())?;
+        let new_blob = find_blob(repository, &delta.new_file())?;
+        let is_binary = old_blob.iter().chain(&new_blob).any(Blob::is_binary);
+        let old_content = old_blob.as_ref().map(Blob::content);
+        let new_content = new_blob.as_ref().map(Blob::content);
+        let language = detect_content_language(
+            path,
+            new_content
+                .or(old_content)
+                .filter(|_| !is_binary)
+                .unwrap_or(&[]),
+        );
+        let count = match Patch::from_diff(&diff, index)? {
+            Some(patch) if !is_binary => {
+                count_patch(&patch, old_content, new_content, encoding, language)?
+            }
+            _ => None,
+        };
+        files.push(FileDiff {
+            path: path.to_path_buf(),
+            old_path: renamed_from(&delta),
+            status: match delta.status() {
+                Delta::Added => "added",
+                Delta::Deleted => "deleted",
+                Delta::Renamed => "renamed",
+                Delta::Copied => "copied",
+                _ => "modified",
+            },
+            language,
+            count,
+        });
+    }
+
+    Ok(RevisionDiff {
+        from: Revision {
+            name: from.to_string(),
+            commit: from_commit.id().to_string(),
+        },
+        to: Revision {
+            name: to.to_string(),
+            commit: to_commit.id().to_string(),
+        },
+        files,
+    })
+}
+
+fn renamed_from(delta: &DiffDelta) -> Option<PathBuf> {
+    match delta.status() {
+        Delta::Renamed | Delta::Copied => delta.old_file().path().map(Path::to_path_buf),
+        _ => None,
+    }
+}
+
+/// One side of a change, `None` when the file does not exist on that side
+fn find_blob<'a>(
+    repository: &'a Repository,
+    file: &DiffFile,
+) -> Result<Option<Blob<'a>>, git2::Error> {
+    if file.id().is_zero() {
+        return Ok(None);
+    }
+    repository.find_blob(file.id()).map(Some)
+}
+
+/// Classifies the lines of both sides of the change, then adds up the changed ones.
+/// Returns `None` when either side cannot be decoded.
+fn count_patch(
+    patch: &Patch,
+    old_content: Option<&[u8]>,
+    new_content: Option<&[u8]>,
+    encoding: Option<&'static Encoding>,
+    language: Option<&'static Language>,
+) -> Result<Option<DiffCount>, git2::Error> {
+    let classify = |content: Option<&[u8]>| match content {
+        Some(content) => classify_lines(content, encoding, language).ok(),
+        None => Some(vec![]),
+    };
+    let (Some(old_lines), Some(new_lines)) = (classify(old_content), classify(new_content)) else {
+        return Ok(None);
+    };
+
+    let changed_line = |lines: &[LineCount], number: Option<u32>| {
+        number
+            .and_then(|number| (number as usize).checked_sub(1))
+            .and_then(|index| lines.get(index))
+            .copied()
+            .unwrap_or(LineCount {
+                lines: 1,
+                ..LineCount::new()
+            })
+    };
+    let mut count = DiffCount::default();
+    for hunk in 0..patch.num_hunks() {
+        for line in 0..patch.num_lines_in_hunk(hunk)? {
+            let line = patch.line_in_hunk(hunk, line)?;
+            match line.origin() {
+                '+' => count.added += changed_line(&new_lines, line.new_lineno()),
+                '-' => count.removed += changed_line(&old_lines, line.old_lineno()),
+                _ => {}
+            }
+        }
+    }
+    Ok(Some(count))
+}
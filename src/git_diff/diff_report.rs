@@ -0,0 +1,198 @@
+use ansi_term::Color::{Green, Red, White};
+use num_format::{Locale, ToFormattedString};
+use prettytable::{format, row, Table};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use crate::git_diff::{DiffCount, RevisionDiff};
+use crate::result_printer::json_result_printer::JSON_SCHEMA_VERSION;
+use crate::summarizer::default_summarizer::{DefaultSummarizer, SummaryGrouping};
+use crate::summarizer::Summarizer;
+
+/// Changes of the files sharing a summary key
+struct DiffRow {
+    key: String,
+    files: u64,
+    count: DiffCount,
+}
+
+/// Groups the changed files like `--summary-by`, most changed lines first
+fn summary_rows(diff: &RevisionDiff, grouping: SummaryGrouping) -> Vec<DiffRow> {
+    let mut added = DefaultSummarizer::new(grouping, false);
+    let mut removed = DefaultSummarizer::new(grouping, false);
+    for file in &diff.files {
+        if let Some(count) = file.count {
+            added.append_entry(&file.path, count.added, file.language, &[]);
+            removed.append_entry(&file.path, count.removed, file.language, &[]);
+        }
+    }
+    let removed: HashMap<&str, _> = removed
+        .row_counts()
+        .map(|(key, count, _)| (key, count))
+        .collect();
+    let mut rows: Vec<DiffRow> = added
+        .row_counts()
+        .map(|(key, count, files)| DiffRow {
+            key: key.to_string(),
+            files,
+            count: DiffCount {
+                added: count,
+                removed: removed[key],
+            },
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        let changed = |row: &DiffRow| row.count.added.lines + row.count.removed.lines;
+        changed(b).cmp(&changed(a)).then_with(|| a.key.cmp(&b.key))
+    });
+    rows
+}
+
+fn total(diff: &RevisionDiff) -> DiffCount {
+    let mut total = DiffCount::default();
+    for count in diff.files.iter().filter_map(|file| file.count) {
+        total += count;
+    }
+    total
+}
+
+fn format_change(added: u64, removed: u64) -> String {
+    format!(
+        "+{} -{}",
+        added.to_formatted_string(&Locale::en_GB),
+        removed.to_formatted_string(&Locale::en_GB)
+    )
+}
+
+fn format_net(net: i64) -> String {
+    format!(
+        "{}{}",
+        if net < 0 { "-" } else { "+" },
+        net.unsigned_abs().to_formatted_string(&Locale::en_GB)
+    )
+}
+
+/// Code, comment and blank lines added and removed, such as `code +12 -3`
+fn format_count(count: &DiffCount) -> String {
+    [
+        ("code", count.added.code_lines(), count.removed.code_lines()),
+        (
+            "comment",
+            count.added.comment_lines,
+            count.removed.comment_lines,
+        ),
+        ("blank", count.added.blank_lines, count.removed.blank_lines),
+    ]
+    .iter()
+    .map(|(kind, added, removed)| {
+        format!(
+            "{kind} {} {}",
+            Green.paint(format!("+{}", added.to_formatted_string(&Locale::en_GB))),
+            Red.paint(format!("-{}", removed.to_formatted_string(&Locale::en_GB)))
+        )
+    })
+    .collect::<Vec<String>>()
+    .join(" / ")
+}
+
+/// Writes the changes as text: every file when `verbose`, then a table grouped like the
+/// summary and the totals
+pub fn print_diff_text(diff: &RevisionDiff, grouping: SummaryGrouping, verbose: bool) {
+    println!(
+        "Changes from {} ({:.7}) to {} ({:.7})",
+        diff.from.name, diff.from.commit, diff.to.name, diff.to.commit
+    );
+    if verbose {
+        for file in &diff.files {
+            let name = match &file.old_path {
+                Some(old_path) => format!("{} <- {}", file.path.display(), old_path.display()),
+                None => file.path.display().to_string(),
+            };
+            match &file.count {
+                Some(count) => println!("{} {name} :: {}", file.status, format_count(count)),
+                None => println!(
+                    "{} {name} :: {}",
+                    file.status,
+                    White.dimmed().paint("binary or invalid")
+                ),
+            }
+        }
+    }
+
+    let rows = summary_rows(diff, grouping);
+    if !rows.is_empty() {
+        let mut table = Table::new();
+        table.set_titles(row![
+            grouping.title(),
+            "files",
+            "code",
+            "comment",
+            "blank",
+            "net"
+        ]);
+        for row in &rows {
+            let count = &row.count;
+            table.add_row(row![
+                row.key,
+                row.files,
+                format_change(count.added.code_lines(), count.removed.code_lines()),
+                format_change(count.added.comment_lines, count.removed.comment_lines),
+                format_change(count.added.blank_lines, count.removed.blank_lines),
+                format_net(count.net()[0])
+            ]);
+        }
+        println!(); // Pretty padding
+        table.set_format(*format::consts::FORMAT_CLEAN);
+        table.printstd();
+        println!(); // Pretty padding
+    }
+
+    let total = total(diff);
+    println!(
+        "{} file{} changed :: {} / net {}",
+        diff.files.len(),
+        if diff.files.len() == 1 { "" } else { "s" },
+        format_count(&total),
+        format_net(total.net()[0])
+    );
+}
+
+/// Writes the changes as a single JSON document, see the README for its schema
+pub fn print_diff_json(diff: &RevisionDiff, grouping: SummaryGrouping) {
+    let document = json!({
+        "schema_version": JSON_SCHEMA_VERSION,
+        "type": "diff",
+        "from": { "revision": diff.from.name, "commit": diff.from.commit },
+        "to": { "revision": diff.to.name, "commit": diff.to.commit },
+        "files": diff
+            .files
+            .iter()
+            .map(|file| {
+                let mut node = json!({
+                    "path": file.path.display().to_string(),
+                    "status": file.status,
+                    "language": file.language.map(|l| l.name),
+                    "count": file.count.as_ref().map(DiffCount::as_json),
+                });
+                if let Some(old_path) = &file.old_path {
+                    node["old_path"] = json!(old_path.display().to_string());
+                }
+                node
+            })
+            .collect::<Vec<Value>>(),
+        "summary": {
+            "type": "diff_summary",
+            "grouping": grouping.name(),
+            "rows": summary_rows(diff, grouping)
+                .iter()
+                .map(|row| json!({
+                    "key": row.key,
+                    "files": row.files,
+                    "count": row.count.as_json(),
+                }))
+                .collect::<Vec<Value>>(),
+        },
+        "total": total(diff).as_json(),
+    });
+    println!("{}", serde_json::to_string_pretty(&document).unwrap());
+}
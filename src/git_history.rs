@@ -0,0 +1,202 @@
+pub mod history_report;
+
+use crate::counter_walker::git_files::bytes_to_path;
+use crate::counter_walker::walk_path_result::WalkPathResult;
+use crate::language::detection::detect_content_language;
+use crate::language::Language;
+use crate::line_counter::{count_content, EmbeddedCount, LineCount};
+use crate::summarizer::default_summarizer::{DefaultSummarizer, SummaryGrouping};
+use crate::summarizer::Summarizer;
+use clap::ValueEnum;
+use encoding_rs::Encoding;
+use git2::{Commit, FileMode, ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Which commits of the history are counted
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum HistorySampling {
+    /// Every commit
+    Commit,
+    /// The last commit of each day
+    Day,
+    /// The last commit of each week, weeks starting on Monday
+    Week,
+    /// Tagged commits
+    Tag,
+}
+
+/// The counts of the files of one commit of the history
+pub struct Snapshot {
+    pub commit: String,
+    /// Commit date in the committer's time zone, as `YYYY-MM-DD`
+    pub date: String,
+    /// Commit time in seconds since the Unix epoch
+    pub time: i64,
+    pub tags: Vec<String>,
+    pub result: WalkPathResult,
+    /// Counts grouped like `--summary-by`
+    pub summarizer: DefaultSummarizer,
+}
+
+/// Count of a blob, shared by every snapshot containing it
+struct BlobCount {
+    language: Option<&'static Language>,
+    /// `None` for binary blobs and blobs which cannot be decoded
+    lines: Option<(LineCount, Vec<EmbeddedCount>)>,
+}
+
+/// Counts the files of the commits along the first-parent history of `revision`, oldest
+/// first, keeping the commits selected by `sampling`. Files are read from the repository's
+/// object database, counting each distinct blob once, and selected by `is_counted` given
+/// their path relative to the repository root.
+pub fn walk_history(
+    repository: &Repository,
+    revision: &str,
+    sampling: HistorySampling,
+    encoding: Option<&'static Encoding>,
+    grouping: SummaryGrouping,
+    is_counted: impl Fn(&Path) -> bool,
+) -> Result<Vec<Snapshot>, git2::Error> {
+    let tags = commit_tags(repository)?;
+    let mut revwalk = repository.revwalk()?;
+    revwalk.push(repository.revparse_single(revision)?.peel_to_commit()?.id())?;
+    revwalk.simplify_first_parent()?;
+
+    // The history is walked from the newest commit, so the first commit seen in a period
+    // is its last one
+    let mut periods = HashSet::new();
+    let mut commits = vec![];
+    for id in revwalk {
+        let commit = repository.find_commit(id?)?;
+        let days = local_days(&commit);
+        let selected = match sampling {
+            HistorySampling::Commit => true,
+            HistorySampling::Day => periods.insert(days),
+            // 1970-01-01 was a Thursday, so shifting by 3 days starts weeks on Monday
+            HistorySampling::Week => periods.insert((days + 3).div_euclid(7)),
+            HistorySampling::Tag => tags.contains_key(&commit.id()),
+        };
+        if selected {
+            commits.push(commit);
+        }
+    }
+
+    let mut blobs: HashMap<(Oid, OsString), BlobCount> = HashMap::new();
+    let mut snapshots = vec![];
+    for commit in commits.iter().rev() {
+        let mut result = WalkPathResult::new();
+        let mut summarizer = DefaultSummarizer::new(grouping, false);
+        for (path, id) in tree_files(commit)? {
+            if !is_counted(&path) {
+                continue;
+            }
+            let key = (id, path.file_name().unwrap_or_default().to_os_string());
+            if !blobs.contains_key(&key) {
+                let count = count_blob(repository, id, &path, encoding)?;
+                blobs.insert(key.clone(), count);
+            }
+            let blob = &blobs[&key];
+            match &blob.lines {
+                Some((lines, embedded)) => {
+                    result.line_count += *lines;
+                    if lines.lines == 0 {
+                        result.empty_file_count += 1;
+                    } else {
+                        result.file_count += 1;
+                    }
+                    summarizer.append_entry(&path, *lines, blob.language, embedded);
+                }
+                None => result.error_file_count += 1,
+            }
+        }
+        snapshots.push(Snapshot {
+            commit: commit.id().to_string(),
+            date: format_date(local_days(commit)),
+            time: commit.time().seconds(),
+            tags: tags.get(&commit.id()).cloned().unwrap_or_default(),
+            result,
+            summarizer,
+        });
+    }
+    Ok(snapshots)
+}
+
+/// Paths and blob ids of the regular files of the commit, skipping symlinks and submodules
+fn tree_files(commit: &Commit) -> Result<Vec<(PathBuf, Oid)>, git2::Error> {
+    let mut files = vec![];
+    commit.tree()?.walk(TreeWalkMode::PreOrder, |root, entry| {
+        let is_file =
+            entry.kind() == Some(ObjectType::Blob) && entry.filemode() != i32::from(FileMode::Link);
+        if is_file {
+            files.push((
+                Path::new(root).join(bytes_to_path(entry.name_bytes())),
+                entry.id(),
+            ));
+        }
+        TreeWalkResult::Ok
+    })?;
+    Ok(files)
+}
+
+fn count_blob(
+    repository: &Repository,
+    id: Oid,
+    path: &Path,
+    encoding: Option<&'static Encoding>,
+) -> Result<BlobCount, git2::Error> {
+    let blob = repository.find_blob(id)?;
+    let language = detect_content_language(path, blob.content());
+    let lines = if blob.is_binary() {
+        None
+    } else {
+        count_content(blob.content(), encoding, language).ok()
+    };
+    Ok(BlobCount { language, lines })
+}
+
+/// Tag names of every tagged commit, sorted
+fn commit_tags(repository: &Repository) -> Result<HashMap<Oid, Vec<String>>, git2::Error> {
+    let mut tags: HashMap<Oid, Vec<String>> = HashMap::new();
+    for name in repository.tag_names(None)?.iter().flatten() {
+        let commit = repository
+            .revparse_single(&format!("refs/tags/{name}"))
+            .and_then(|object| object.peel_to_commit());
+        if let Ok(commit) = commit {
+            tags.entry(commit.id()).or_default().push(name.to_string());
+        }
+    }
+    for names in tags.values_mut() {
+        names.sort();
+    }
+    Ok(tags)
+}
+
+/// Days since the Unix epoch at the commit time, in the committer's time zone
+fn local_days(commit: &Commit) -> i64 {
+    let time = commit.time();
+    (time.seconds() + i64::from(time.offset_minutes()) * 60).div_euclid(SECONDS_PER_DAY)
+}
+
+/// Formats days since the Unix epoch as a `YYYY-MM-DD` date of the proleptic Gregorian calendar
+fn format_date(days: i64) -> String {
+    // Shift the epoch to 0000-03-01, so that leap days end each 400 year era
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = era * 400 + year_of_era + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
@@ -0,0 +1,126 @@
+use num_format::{Locale, ToFormattedString};
+use prettytable::{format, row, Table};
+use serde_json::{json, Value};
+use std::io::stdout;
+
+use crate::git_history::{HistorySampling, Snapshot};
+use crate::line_counter::LineCount;
+use crate::result_printer::json_result_printer::JSON_SCHEMA_VERSION;
+use crate::summarizer::default_summarizer::SummaryGrouping;
+
+/// Counts of the files sharing a summary key, most lines first
+fn summary_rows(snapshot: &Snapshot) -> Vec<(&str, LineCount, u64)> {
+    let mut rows: Vec<_> = snapshot.summarizer.row_counts().collect();
+    rows.sort_by(|a, b| b.1.lines.cmp(&a.1.lines).then_with(|| a.0.cmp(b.0)));
+    rows
+}
+
+/// Writes a table with the totals of each snapshot, oldest first
+pub fn print_history_text(snapshots: &[Snapshot]) {
+    let mut table = Table::new();
+    table.set_titles(row![
+        "date", "commit", "tags", "files", "lines", "code", "comment", "blank"
+    ]);
+    for snapshot in snapshots {
+        let count = &snapshot.result.line_count;
+        let format = |n: u64| n.to_formatted_string(&Locale::en_GB);
+        table.add_row(row![
+            snapshot.date,
+            format!("{:.7}", snapshot.commit),
+            snapshot.tags.join(", "),
+            format(snapshot.result.file_count as u64),
+            format(count.lines),
+            format(count.code_lines()),
+            format(count.comment_lines),
+            format(count.blank_lines)
+        ]);
+    }
+    table.set_format(*format::consts::FORMAT_CLEAN);
+    table.printstd();
+}
+
+/// Writes one delimited row per summary key of each snapshot, oldest first
+pub fn print_history_csv(snapshots: &[Snapshot], grouping: SummaryGrouping, delimiter: u8) {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(stdout());
+    writer
+        .write_record([
+            "commit",
+            "date",
+            "tags",
+            grouping.name(),
+            "files",
+            "lines",
+            "code",
+            "comment",
+            "blank",
+            "bytes",
+        ])
+        .expect("Failed to write output");
+    for snapshot in snapshots {
+        let tags = snapshot.tags.join(" ");
+        for (key, count, files) in summary_rows(snapshot) {
+            let counts = [
+                files,
+                count.lines,
+                count.code_lines(),
+                count.comment_lines,
+                count.blank_lines,
+                count.bytes,
+            ]
+            .map(|n| n.to_string());
+            writer
+                .write_record(
+                    [&snapshot.commit, &snapshot.date, &tags, key]
+                        .into_iter()
+                        .chain(counts.iter().map(String::as_str)),
+                )
+                .expect("Failed to write output");
+        }
+    }
+    writer.flush().expect("Failed to write output");
+}
+
+/// Writes the snapshots as a single JSON document, see the README for its schema
+pub fn print_history_json(
+    snapshots: &[Snapshot],
+    sampling: HistorySampling,
+    grouping: SummaryGrouping,
+) {
+    let document = json!({
+        "schema_version": JSON_SCHEMA_VERSION,
+        "type": "history",
+        "sampling": match sampling {
+            HistorySampling::Commit => "commit",
+            HistorySampling::Day => "day",
+            HistorySampling::Week => "week",
+            HistorySampling::Tag => "tag",
+        },
+        "grouping": grouping.name(),
+        "snapshots": snapshots
+            .iter()
+            .map(|snapshot| json!({
+                "commit": snapshot.commit,
+                "date": snapshot.date,
+                "time": snapshot.time,
+                "tags": snapshot.tags,
+                "result": {
+                    "line_count": snapshot.result.line_count.as_json(),
+                    "file_count": snapshot.result.file_count,
+                    "empty_file_count": snapshot.result.empty_file_count,
+                    "error_file_count": snapshot.result.error_file_count,
+                },
+                "rows": summary_rows(snapshot)
+                    .iter()
+                    .map(|(key, count, files)| json!({
+                        "key": key,
+                        "files": files,
+                        "count": count.as_json(),
+                    }))
+                    .collect::<Vec<Value>>(),
+            }))
+            .collect::<Vec<Value>>(),
+    });
+    println!("{}", serde_json::to_string_pretty(&document).unwrap());
+}
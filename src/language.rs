@@ -0,0 +1,606 @@
+pub mod definitions;
+pub mod detection;
+pub mod heuristics;
+
+use std::ffi::OsStr;
+use std::sync::OnceLock;
+
+/// Comment syntax of a programming language, used to classify lines as code, comment or blank
+#[derive(Debug)]
+pub struct Language {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+    /// Exact file names belonging to the language, such as `Makefile`
+    pub file_names: &'static [&'static str],
+    /// Interpreter names recognised in a shebang line, such as `python3`
+    pub interpreters: &'static [&'static str],
+    pub line_comments: &'static [&'static str],
+    pub block_comments: &'static [(&'static str, &'static str)],
+    /// Whether block comments can be nested inside each other
+    pub nested_comments: bool,
+    /// String literal delimiters, in which backslash escapes the next character
+    pub strings: &'static [(&'static str, &'static str)],
+    /// Whether a single character between `'` quotes, or an escape, is a character literal.
+    /// A `'` which does not start one, such as that of a Rust lifetime, is plain code.
+    pub char_literals: bool,
+    /// String literal delimiters without escape sequences
+    pub raw_strings: &'static [(&'static str, &'static str)],
+}
+
+const C_BLOCK: &[(&str, &str)] = &[("/*", "*/")];
+const XML_BLOCK: &[(&str, &str)] = &[("<!--", "-->")];
+const DOUBLE_QUOTES: &[(&str, &str)] = &[("\"", "\"")];
+const QUOTES: &[(&str, &str)] = &[("\"", "\""), ("'", "'")];
+const JS_QUOTES: &[(&str, &str)] = &[("\"", "\""), ("'", "'"), ("`", "`")];
+const PYTHON_QUOTES: &[(&str, &str)] = &[
+    ("\"\"\"", "\"\"\""),
+    ("'''", "'''"),
+    ("\"", "\""),
+    ("'", "'"),
+];
+
+pub static BUILTIN_LANGUAGES: &[Language] = &[
+    Language {
+        name: "Rust",
+        extensions: &["rs"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: true,
+        strings: DOUBLE_QUOTES,
+        char_literals: true,
+        raw_strings: &[
+            ("r\"", "\""),
+            ("r#\"", "\"#"),
+            ("r##\"", "\"##"),
+            ("r###\"", "\"###"),
+        ],
+    },
+    Language {
+        name: "C",
+        extensions: &["c", "h"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "C++",
+        extensions: &["cpp", "cc", "cxx", "c++", "hpp", "hh", "hxx", "inl", "h"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[("R\"(", ")\"")],
+    },
+    Language {
+        name: "C#",
+        extensions: &["cs"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[("@\"", "\""), ("\"\"\"", "\"\"\"")],
+    },
+    Language {
+        name: "Java",
+        extensions: &["java"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[("\"\"\"", "\"\"\"")],
+    },
+    Language {
+        name: "Kotlin",
+        extensions: &["kt", "kts"],
+        file_names: &[],
+        interpreters: &["kotlin"],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: true,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[("\"\"\"", "\"\"\"")],
+    },
+    Language {
+        name: "Scala",
+        extensions: &["scala", "sc"],
+        file_names: &[],
+        interpreters: &["scala"],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: true,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[("\"\"\"", "\"\"\"")],
+    },
+    Language {
+        name: "Go",
+        extensions: &["go"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[("`", "`")],
+    },
+    Language {
+        name: "Swift",
+        extensions: &["swift"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: true,
+        strings: DOUBLE_QUOTES,
+        char_literals: false,
+        raw_strings: &[("#\"", "\"#"), ("\"\"\"", "\"\"\"")],
+    },
+    Language {
+        name: "JavaScript",
+        extensions: &["js", "mjs", "cjs", "jsx"],
+        file_names: &[],
+        interpreters: &["node", "nodejs", "deno", "bun"],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: JS_QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "TypeScript",
+        extensions: &["ts", "mts", "cts", "tsx"],
+        file_names: &[],
+        interpreters: &["ts-node", "tsx"],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: JS_QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "CSS",
+        extensions: &["css"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &[],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "SCSS",
+        extensions: &["scss", "less"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "PHP",
+        extensions: &["php"],
+        file_names: &[],
+        interpreters: &["php"],
+        line_comments: &["//", "#"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Python",
+        extensions: &["py", "pyw", "pyi"],
+        file_names: &["SConstruct", "SConscript"],
+        interpreters: &["python", "python2", "python3", "pypy", "pypy3"],
+        line_comments: &["#"],
+        block_comments: &[],
+        nested_comments: false,
+        strings: PYTHON_QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Ruby",
+        extensions: &["rb"],
+        file_names: &["Rakefile", "Gemfile", "Guardfile", "Vagrantfile"],
+        interpreters: &["ruby"],
+        line_comments: &["#"],
+        block_comments: &[("=begin", "=end")],
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Perl",
+        extensions: &["pl", "pm"],
+        file_names: &[],
+        interpreters: &["perl"],
+        line_comments: &["#"],
+        block_comments: &[],
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Shell",
+        extensions: &["sh", "bash", "zsh", "ksh", "fish"],
+        file_names: &[".bashrc", ".bash_profile", ".zshrc", ".profile"],
+        interpreters: &["sh", "bash", "zsh", "ksh", "dash", "fish"],
+        line_comments: &["#"],
+        block_comments: &[],
+        nested_comments: false,
+        strings: DOUBLE_QUOTES,
+        char_literals: false,
+        raw_strings: &[("'", "'")],
+    },
+    Language {
+        name: "PowerShell",
+        extensions: &["ps1", "psm1"],
+        file_names: &[],
+        interpreters: &["pwsh", "powershell"],
+        line_comments: &["#"],
+        block_comments: &[("<#", "#>")],
+        nested_comments: false,
+        strings: DOUBLE_QUOTES,
+        char_literals: false,
+        raw_strings: &[("'", "'")],
+    },
+    Language {
+        name: "R",
+        extensions: &["r"],
+        file_names: &[],
+        interpreters: &["Rscript"],
+        line_comments: &["#"],
+        block_comments: &[],
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "YAML",
+        extensions: &["yml", "yaml"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["#"],
+        block_comments: &[],
+        nested_comments: false,
+        strings: &[],
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "TOML",
+        extensions: &["toml"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["#"],
+        block_comments: &[],
+        nested_comments: false,
+        strings: DOUBLE_QUOTES,
+        char_literals: false,
+        raw_strings: &[("'", "'")],
+    },
+    Language {
+        name: "SQL",
+        extensions: &["sql"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["--"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: &[],
+        char_literals: false,
+        raw_strings: &[("'", "'"), ("\"", "\"")],
+    },
+    Language {
+        name: "Lua",
+        extensions: &["lua"],
+        file_names: &[],
+        interpreters: &["lua", "luajit"],
+        line_comments: &["--"],
+        block_comments: &[("--[[", "]]")],
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[("[[", "]]")],
+    },
+    Language {
+        name: "Haskell",
+        extensions: &["hs"],
+        file_names: &[],
+        interpreters: &["runhaskell", "runghc"],
+        line_comments: &["--"],
+        block_comments: &[("{-", "-}")],
+        nested_comments: true,
+        strings: DOUBLE_QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "HTML",
+        extensions: &["html", "htm"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &[],
+        block_comments: XML_BLOCK,
+        nested_comments: false,
+        strings: &[],
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "XML",
+        extensions: &["xml", "xsd", "xsl", "svg"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &[],
+        block_comments: XML_BLOCK,
+        nested_comments: false,
+        strings: &[],
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Markdown",
+        extensions: &["md", "markdown"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &[],
+        block_comments: XML_BLOCK,
+        nested_comments: false,
+        strings: &[],
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Makefile",
+        extensions: &["mk", "mak"],
+        file_names: &["Makefile", "makefile", "GNUmakefile"],
+        interpreters: &["make"],
+        line_comments: &["#"],
+        block_comments: &[],
+        nested_comments: false,
+        strings: &[],
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Dockerfile",
+        extensions: &["dockerfile"],
+        file_names: &["Dockerfile", "Containerfile"],
+        interpreters: &[],
+        line_comments: &["#"],
+        block_comments: &[],
+        nested_comments: false,
+        strings: &[],
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Groovy",
+        extensions: &["groovy", "gradle"],
+        file_names: &["Jenkinsfile"],
+        interpreters: &["groovy"],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "CMake",
+        extensions: &["cmake"],
+        file_names: &["CMakeLists.txt"],
+        interpreters: &[],
+        line_comments: &["#"],
+        block_comments: &[("#[[", "]]")],
+        nested_comments: false,
+        strings: DOUBLE_QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Objective-C",
+        extensions: &["m", "mm", "h"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "MATLAB",
+        extensions: &["m"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["%"],
+        block_comments: &[("%{", "%}")],
+        nested_comments: true,
+        strings: DOUBLE_QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Prolog",
+        extensions: &["pl", "pro"],
+        file_names: &[],
+        interpreters: &["swipl"],
+        line_comments: &["%"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Qt Linguist",
+        extensions: &["ts"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &[],
+        block_comments: XML_BLOCK,
+        nested_comments: false,
+        strings: &[],
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Verilog",
+        extensions: &["v", "vh", "sv", "svh"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &["//"],
+        block_comments: C_BLOCK,
+        nested_comments: false,
+        strings: DOUBLE_QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Coq",
+        extensions: &["v"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &[],
+        block_comments: &[("(*", "*)")],
+        nested_comments: true,
+        strings: DOUBLE_QUOTES,
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Vue",
+        extensions: &["vue"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &[],
+        block_comments: XML_BLOCK,
+        nested_comments: false,
+        strings: &[],
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Svelte",
+        extensions: &["svelte"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &[],
+        block_comments: XML_BLOCK,
+        nested_comments: false,
+        strings: &[],
+        char_literals: false,
+        raw_strings: &[],
+    },
+    Language {
+        name: "Jupyter Notebook",
+        extensions: &["ipynb"],
+        file_names: &[],
+        interpreters: &[],
+        line_comments: &[],
+        block_comments: &[],
+        nested_comments: false,
+        strings: &[],
+        char_literals: false,
+        raw_strings: &[],
+    },
+];
+
+static LANGUAGES: OnceLock<Vec<&'static Language>> = OnceLock::new();
+
+/// Registers user defined languages, given from lowest to highest priority, ahead of the
+/// built-in ones. A user language replaces any earlier language of the same name.
+pub fn init_languages(user_languages: Vec<Language>) {
+    let mut languages: Vec<&'static Language> = vec![];
+    for language in user_languages.into_iter().rev() {
+        if !languages.iter().any(|l| l.name == language.name) {
+            languages.push(Box::leak(Box::new(language)));
+        }
+    }
+    for language in BUILTIN_LANGUAGES {
+        if !languages.iter().any(|l| l.name == language.name) {
+            languages.push(language);
+        }
+    }
+    let _ = LANGUAGES.set(languages);
+}
+
+impl Language {
+    /// All known languages, in lookup priority order
+    pub fn all() -> &'static [&'static Language] {
+        LANGUAGES.get_or_init(|| BUILTIN_LANGUAGES.iter().collect())
+    }
+
+    /// Looks up a language by file extension, ignoring case
+    pub fn from_extension(extension: &OsStr) -> Option<&'static Language> {
+        let extension = extension.to_str()?.to_ascii_lowercase();
+        Self::all()
+            .iter()
+            .find(|language| language.extensions.contains(&&*extension))
+            .copied()
+    }
+
+    /// Looks up a language by its exact file name
+    pub fn from_exact_file_name(file_name: &OsStr) -> Option<&'static Language> {
+        let name = file_name.to_str()?;
+        Self::all()
+            .iter()
+            .find(|language| language.file_names.contains(&name))
+            .copied()
+    }
+
+    /// Looks up a language by name, ignoring case
+    pub fn from_name(name: &str) -> Option<&'static Language> {
+        Self::all()
+            .iter()
+            .find(|language| language.name.eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    /// Looks up a language by a loose alias such as `rust`, `py` or `bash`,
+    /// as used by modelines and code fences
+    pub fn from_alias(alias: &str) -> Option<&'static Language> {
+        let alias = alias.to_ascii_lowercase();
+        Self::all()
+            .iter()
+            .find(|language| {
+                language.name.eq_ignore_ascii_case(&alias)
+                    || language.extensions.contains(&&*alias)
+                    || language.interpreters.contains(&&*alias)
+            })
+            .copied()
+    }
+}
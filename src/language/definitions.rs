@@ -0,0 +1,109 @@
+use crate::language::Language;
+use serde::Deserialize;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+const DEFINITIONS_FILE_NAME: &str = "languages.toml";
+
+#[derive(Deserialize)]
+struct DefinitionsFile {
+    #[serde(default)]
+    language: Vec<LanguageDefinition>,
+}
+
+/// User supplied language, as written in a `languages.toml` file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LanguageDefinition {
+    name: String,
+    #[serde(default)]
+    extensions: Vec<String>,
+    #[serde(default)]
+    file_names: Vec<String>,
+    #[serde(default)]
+    interpreters: Vec<String>,
+    #[serde(default)]
+    line_comments: Vec<String>,
+    #[serde(default)]
+    block_comments: Vec<(String, String)>,
+    #[serde(default)]
+    nested_comments: bool,
+    #[serde(default)]
+    strings: Vec<(String, String)>,
+    #[serde(default)]
+    char_literals: bool,
+    #[serde(default)]
+    raw_strings: Vec<(String, String)>,
+}
+
+fn leak_str(s: String) -> &'static str {
+    Box::leak(s.into_boxed_str())
+}
+
+fn leak_strs(strs: Vec<String>) -> &'static [&'static str] {
+    Box::leak(strs.into_iter().map(leak_str).collect())
+}
+
+fn leak_pairs(pairs: Vec<(String, String)>) -> &'static [(&'static str, &'static str)] {
+    Box::leak(
+        pairs
+            .into_iter()
+            .map(|(start, end)| (leak_str(start), leak_str(end)))
+            .collect(),
+    )
+}
+
+impl From<LanguageDefinition> for Language {
+    fn from(definition: LanguageDefinition) -> Self {
+        Language {
+            name: leak_str(definition.name),
+            extensions: leak_strs(
+                definition
+                    .extensions
+                    .into_iter()
+                    .map(|e| e.trim_start_matches('.').to_ascii_lowercase())
+                    .collect(),
+            ),
+            file_names: leak_strs(definition.file_names),
+            interpreters: leak_strs(definition.interpreters),
+            line_comments: leak_strs(definition.line_comments),
+            block_comments: leak_pairs(definition.block_comments),
+            nested_comments: definition.nested_comments,
+            strings: leak_pairs(definition.strings),
+            char_literals: definition.char_literals,
+            raw_strings: leak_pairs(definition.raw_strings),
+        }
+    }
+}
+
+/// Parses a single language definitions file
+pub fn load_definitions(path: &Path) -> Result<Vec<Language>, Error> {
+    let content = fs::read_to_string(path)?;
+    let file: DefinitionsFile = toml::from_str(&content).map_err(|err| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), err.message()),
+        )
+    })?;
+    Ok(file.language.into_iter().map(Language::from).collect())
+}
+
+/// Default definition file locations, from lowest to highest priority
+pub fn default_definition_paths() -> Vec<PathBuf> {
+    let mut paths = vec![];
+    if let Some(dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        paths.push(dir.join(DEFINITIONS_FILE_NAME));
+    }
+    if let Some(home) = std::env::home_dir() {
+        paths.push(
+            home.join(".config")
+                .join("lnav")
+                .join(DEFINITIONS_FILE_NAME),
+        );
+    }
+    paths
+}
@@ -0,0 +1,176 @@
+use crate::language::heuristics::{disambiguate, is_ambiguous_extension};
+use crate::language::Language;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const HEAD_SIZE: u64 = 8192;
+const TAIL_SIZE: u64 = 1024;
+const MODELINE_LINES: usize = 5;
+
+/// Detects the language of a file from its name, then its extension (sniffing the content
+/// for extensions shared by several languages), then its shebang line and finally vim or
+/// emacs modelines. When the content cannot be read, an ambiguous extension gives its
+/// default language and a file without a known extension is treated as unknown.
+pub fn detect_language(path: &Path) -> Option<&'static Language> {
+    detect(path, || read_head_and_tail(path))
+}
+
+/// Detects the language of a file whose content is held in memory, such as a git blob
+pub fn detect_content_language(path: &Path, content: &[u8]) -> Option<&'static Language> {
+    detect(path, || Some(content_head_and_tail(content)))
+}
+
+fn detect(
+    path: &Path,
+    read_head_and_tail: impl Fn() -> Option<(String, String)>,
+) -> Option<&'static Language> {
+    if let Some(language) = path.file_name().and_then(Language::from_exact_file_name) {
+        return Some(language);
+    }
+    let extension = path.extension();
+    if let Some(extension) = extension.and_then(OsStr::to_str) {
+        if is_ambiguous_extension(extension) {
+            if let Some(language) =
+                read_head_and_tail().and_then(|(head, _)| disambiguate(extension, &head))
+            {
+                return Some(language);
+            }
+        }
+    }
+    if let Some(language) = extension.and_then(Language::from_extension) {
+        return Some(language);
+    }
+    let (head, tail) = read_head_and_tail()?;
+    let head_lines: Vec<&str> = head.lines().take(MODELINE_LINES).collect();
+    let tail_lines: Vec<&str> = tail.lines().rev().take(MODELINE_LINES).collect();
+
+    if let Some(language) = head_lines
+        .first()
+        .and_then(|line| language_from_shebang(line))
+    {
+        return Some(language);
+    }
+    head_lines
+        .iter()
+        .take(2)
+        .find_map(|line| emacs_modeline(line))
+        .or_else(|| {
+            head_lines
+                .iter()
+                .chain(tail_lines.iter())
+                .find_map(|line| vim_modeline(line))
+        })
+        .and_then(language_from_mode)
+}
+
+fn read_head_and_tail(path: &Path) -> Option<(String, String)> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let mut head = vec![];
+    (&mut file).take(HEAD_SIZE).read_to_end(&mut head).ok()?;
+    let mut tail = vec![];
+    if len > HEAD_SIZE {
+        file.seek(SeekFrom::Start(
+            len.saturating_sub(TAIL_SIZE).max(HEAD_SIZE),
+        ))
+        .ok()?;
+        file.read_to_end(&mut tail).ok()?;
+    }
+    Some((
+        String::from_utf8_lossy(&head).into_owned(),
+        String::from_utf8_lossy(&tail).into_owned(),
+    ))
+}
+
+fn content_head_and_tail(content: &[u8]) -> (String, String) {
+    let head_end = content.len().min(HEAD_SIZE as usize);
+    let tail_start = content
+        .len()
+        .saturating_sub(TAIL_SIZE as usize)
+        .max(head_end);
+    (
+        String::from_utf8_lossy(&content[..head_end]).into_owned(),
+        String::from_utf8_lossy(&content[tail_start..]).into_owned(),
+    )
+}
+
+/// Resolves the interpreter of a `#!` line, looking through `/usr/bin/env`
+fn language_from_shebang(line: &str) -> Option<&'static Language> {
+    let mut parts = line.strip_prefix("#!")?.split_whitespace();
+    let mut interpreter = parts.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        interpreter = parts.find(|part| !part.starts_with('-') && !part.contains('='))?;
+    }
+    language_from_interpreter(interpreter).or_else(|| {
+        language_from_interpreter(
+            interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.'),
+        )
+    })
+}
+
+fn language_from_interpreter(interpreter: &str) -> Option<&'static Language> {
+    Language::all()
+        .iter()
+        .find(|language| language.interpreters.contains(&interpreter))
+        .copied()
+}
+
+/// Extracts the mode of an emacs `-*- mode: name -*-` or `-*- name -*-` line
+fn emacs_modeline(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once("-*-")?;
+    let (inner, _) = rest.split_once("-*-")?;
+    if !inner.contains(':') {
+        return Some(inner.trim());
+    }
+    inner.split(';').find_map(|variable| {
+        let (key, value) = variable.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case("mode")
+            .then_some(value.trim())
+    })
+}
+
+/// Extracts the file type of a vim `vim: set ft=name:` modeline
+fn vim_modeline(line: &str) -> Option<&str> {
+    let start = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|(pos, _)| *pos == 0 || line[..*pos].ends_with(char::is_whitespace))
+            .map(|(pos, marker)| pos + marker.len())
+    })?;
+    line[start..]
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .find_map(|option| {
+            let (key, value) = option.split_once('=')?;
+            matches!(key, "ft" | "filetype" | "syn" | "syntax").then_some(value)
+        })
+}
+
+/// Resolves a modeline mode against language names, extensions and interpreters
+fn language_from_mode(mode: &str) -> Option<&'static Language> {
+    Language::from_alias(mode.strip_suffix("-mode").unwrap_or(mode))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreadable_ambiguous_extensions_fall_back_to_their_default() {
+        let missing = std::env::temp_dir().join(format!("lnav-missing-{}", std::process::id()));
+        let name = |extension: &str| {
+            detect_language(&missing.with_extension(extension)).map(|language| language.name)
+        };
+        assert_eq!(name("h"), Some("C"));
+        assert_eq!(name("pl"), Some("Perl"));
+        assert_eq!(name("ts"), Some("TypeScript"));
+        assert_eq!(name("unknown"), None);
+    }
+
+    #[test]
+    fn readable_ambiguous_extensions_are_sniffed() {
+        let language = detect_content_language(Path::new("a.pl"), b":- module(a, []).\n");
+        assert_eq!(language.map(|language| language.name), Some("Prolog"));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::language::Language;
+
+/// Content markers which identify one of the languages sharing an extension
+struct Rule {
+    language: &'static str,
+    markers: &'static [&'static str],
+}
+
+/// Languages sharing an extension, tried in order, falling back to `default`
+struct Heuristic {
+    extension: &'static str,
+    rules: &'static [Rule],
+    default: &'static str,
+}
+
+static HEURISTICS: &[Heuristic] = &[
+    Heuristic {
+        extension: "h",
+        rules: &[
+            Rule {
+                language: "Objective-C",
+                markers: &[
+                    "@interface",
+                    "@implementation",
+                    "@protocol",
+                    "@property",
+                    "#import",
+                ],
+            },
+            Rule {
+                language: "C++",
+                markers: &[
+                    "class ",
+                    "namespace ",
+                    "template<",
+                    "template <",
+                    "std::",
+                    "public:",
+                    "private:",
+                    "protected:",
+                    "#include <iostream>",
+                    "#include <string>",
+                    "#include <vector>",
+                ],
+            },
+        ],
+        default: "C",
+    },
+    Heuristic {
+        extension: "m",
+        rules: &[
+            Rule {
+                language: "Objective-C",
+                markers: &[
+                    "@interface",
+                    "@implementation",
+                    "@end",
+                    "#import",
+                    "#include",
+                ],
+            },
+            Rule {
+                language: "MATLAB",
+                markers: &["\nfunction ", "\n%", "\nend", "disp(", "zeros(", "fprintf("],
+            },
+        ],
+        default: "Objective-C",
+    },
+    Heuristic {
+        extension: "pl",
+        rules: &[
+            Rule {
+                language: "Perl",
+                markers: &[
+                    "use strict",
+                    "use warnings",
+                    "my $",
+                    "\nsub ",
+                    "#!/usr/bin/perl",
+                ],
+            },
+            Rule {
+                language: "Prolog",
+                markers: &[":- ", ":-\n", "\n:-"],
+            },
+        ],
+        default: "Perl",
+    },
+    Heuristic {
+        extension: "ts",
+        rules: &[Rule {
+            language: "Qt Linguist",
+            markers: &["<!DOCTYPE TS>", "<TS ", "<TS>"],
+        }],
+        default: "TypeScript",
+    },
+    Heuristic {
+        extension: "v",
+        rules: &[
+            Rule {
+                language: "Coq",
+                markers: &[
+                    "Require Import",
+                    "Theorem ",
+                    "Lemma ",
+                    "Proof.",
+                    "Qed.",
+                    "Inductive ",
+                    "Fixpoint ",
+                ],
+            },
+            Rule {
+                language: "Verilog",
+                markers: &[
+                    "module ",
+                    "endmodule",
+                    "always @",
+                    "wire ",
+                    "reg ",
+                    "`timescale",
+                ],
+            },
+        ],
+        default: "Verilog",
+    },
+];
+
+fn heuristic_for(extension: &str) -> Option<&'static Heuristic> {
+    HEURISTICS
+        .iter()
+        .find(|heuristic| heuristic.extension.eq_ignore_ascii_case(extension))
+}
+
+/// Whether files with the extension are assigned a language based on their content
+pub fn is_ambiguous_extension(extension: &str) -> bool {
+    heuristic_for(extension).is_some()
+}
+
+/// Picks between the languages sharing an extension by sniffing the start of the file.
+/// Returns `None` when the extension is not ambiguous or has been claimed by a user
+/// defined language.
+pub fn disambiguate(extension: &str, head: &str) -> Option<&'static Language> {
+    let heuristic = heuristic_for(extension)?;
+    let candidates: Vec<&str> = heuristic
+        .rules
+        .iter()
+        .map(|rule| rule.language)
+        .chain([heuristic.default])
+        .collect();
+    let by_extension = Language::from_extension(extension.as_ref())?;
+    if !candidates.contains(&by_extension.name) {
+        return None;
+    }
+
+    let head = format!("\n{head}");
+    let name = heuristic
+        .rules
+        .iter()
+        .find(|rule| rule.markers.iter().any(|marker| head.contains(marker)))
+        .map_or(heuristic.default, |rule| rule.language);
+    Language::from_name(name)
+}
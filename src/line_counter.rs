@@ -1,6 +1,7 @@
+use std::borrow::Cow;
 use std::fmt;
 use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, Error, Read};
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Seek};
 use std::ops::{Add, AddAssign};
 use std::path::Path;
 
@@ -9,9 +10,16 @@ use chardet::detect;
 use encoding_rs::{Encoding, UTF_8};
 use encoding_rs_io::DecodeReaderBytesBuilder;
 use humansize::{format_size, WINDOWS};
+use memchr::memchr_iter;
 use num_format::{Locale, ToFormattedString};
+use serde_json::{json, Value};
 
-use crate::line_processor::LineProcessorFactory;
+use crate::language::Language;
+use crate::line_processor::{LineProcessor, LineProcessorFactory};
+
+const CHUNK_SIZE: usize = 256 * 1024;
+const DETECTION_SIZE: usize = 8192;
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
 
 #[derive(Clone, Copy, Debug)]
 pub enum LineCountFormat {
@@ -23,9 +31,17 @@ pub enum LineCountFormat {
 pub struct LineCount {
     pub lines: u64,
     pub blank_lines: u64,
+    pub comment_lines: u64,
     pub bytes: u64,
 }
 
+/// Lines of a file written in another language, such as a code block in Markdown
+#[derive(Clone, Copy, Debug)]
+pub struct EmbeddedCount {
+    pub language: &'static Language,
+    pub count: LineCount,
+}
+
 impl fmt::Display for LineCount {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.as_simple(false))
@@ -43,10 +59,16 @@ impl LineCount {
         LineCount {
             lines: 0,
             blank_lines: 0,
+            comment_lines: 0,
             bytes: 0,
         }
     }
 
+    /// Lines which are neither blank nor comments
+    pub fn code_lines(&self) -> u64 {
+        self.lines - self.blank_lines - self.comment_lines
+    }
+
     fn as_simple(&self, show_bytes: bool) -> String {
         let loc_formatted = self.lines.to_string();
         if show_bytes {
@@ -62,11 +84,14 @@ impl LineCount {
         if show_bytes {
             let bytes_formatted = format_size(self.bytes, WINDOWS);
             let blank_formatted = self.blank_lines.to_formatted_string(&Locale::en);
+            let comment_formatted = self.comment_lines.to_formatted_string(&Locale::en);
             format!(
-                "{} {}{} {}",
+                "{} {}{} {}{} {}",
                 Blue.paint(loc_formatted),
                 White.dimmed().paint(blank_formatted),
                 White.dimmed().paint(" blank"),
+                White.dimmed().paint(comment_formatted),
+                White.dimmed().paint(" comment"),
                 White.dimmed().paint(bytes_formatted),
             )
         } else {
@@ -74,6 +99,16 @@ impl LineCount {
         }
     }
 
+    pub fn as_json(&self) -> Value {
+        json!({
+            "lines": self.lines,
+            "code_lines": self.code_lines(),
+            "comment_lines": self.comment_lines,
+            "blank_lines": self.blank_lines,
+            "bytes": self.bytes,
+        })
+    }
+
     pub fn as_fmt_string(&self, display_format: &LineCountFormat) -> String {
         match display_format {
             LineCountFormat::Simple { show_bytes } => self.as_simple(show_bytes.to_owned()),
@@ -89,6 +124,7 @@ impl Add for LineCount {
         LineCount {
             lines: self.lines + rhs.lines,
             blank_lines: self.blank_lines + rhs.blank_lines,
+            comment_lines: self.comment_lines + rhs.comment_lines,
             bytes: self.bytes + rhs.bytes,
         }
     }
@@ -98,6 +134,7 @@ impl AddAssign for LineCount {
     fn add_assign(&mut self, rhs: Self) {
         self.lines += rhs.lines;
         self.blank_lines += rhs.blank_lines;
+        self.comment_lines += rhs.comment_lines;
         self.bytes += rhs.bytes;
     }
 }
@@ -106,34 +143,194 @@ impl AddAssign<LineCount> for &mut LineCount {
     fn add_assign(&mut self, rhs: LineCount) {
         self.lines += rhs.lines;
         self.blank_lines += rhs.blank_lines;
+        self.comment_lines += rhs.comment_lines;
         self.bytes += rhs.bytes;
     }
 }
 
-pub fn count_lines(file: &Path, encoding: &'static Encoding) -> Result<LineCount, Error> {
+pub fn count_lines(
+    file: &Path,
+    encoding: &'static Encoding,
+    language: Option<&'static Language>,
+) -> Result<(LineCount, Vec<EmbeddedCount>), Error> {
     match File::open(file) {
-        Ok(fp) => {
-            let mut line_processor = LineProcessorFactory::create(file.file_name().unwrap());
+        Ok(mut fp) => {
             let bytes = fp.metadata().unwrap().len();
-            let reader = BufReader::new(
-                DecodeReaderBytesBuilder::new()
-                    .encoding(Some(encoding))
-                    .build(fp),
-            );
-            let mut count = LineCount::new();
-            count.bytes = bytes;
-            for line_result in reader.lines() {
-                match line_result {
-                    Ok(line) => count += line_processor.process_line(&line, encoding)?,
-                    Err(err) => return Err(err),
+            if encoding.is_ascii_compatible() {
+                let mut line_processor = LineProcessorFactory::create(language);
+                if let Some(mut count) = count_lines_fast(&mut fp, encoding, &mut *line_processor)?
+                {
+                    count.bytes = bytes;
+                    count += line_processor.finish(encoding)?;
+                    return Ok((count, line_processor.embedded()));
                 }
+                fp.rewind()?;
             }
-            Ok(count)
+            let (mut count, embedded) = count_lines_decoded(fp, encoding, language)?;
+            count.bytes = bytes;
+            Ok((count, embedded))
         }
         Err(err) => Err(err),
     }
 }
 
+/// Counts lines by decoding the file to text, which works for any encoding. A byte order mark
+/// overrides the encoding, as it does for the fast path and for [count_content].
+fn count_lines_decoded(
+    fp: File,
+    encoding: &'static Encoding,
+    language: Option<&'static Language>,
+) -> Result<(LineCount, Vec<EmbeddedCount>), Error> {
+    let mut line_processor = LineProcessorFactory::create(language);
+    let reader = BufReader::new(
+        DecodeReaderBytesBuilder::new()
+            .encoding(Some(encoding))
+            .bom_override(true)
+            .build(fp),
+    );
+    let mut count = LineCount::new();
+    for line_result in reader.lines() {
+        match line_result {
+            Ok(line) => count += line_processor.process_line(&line, encoding)?,
+            Err(err) => return Err(err),
+        }
+    }
+    count += line_processor.finish(encoding)?;
+    Ok((count, line_processor.embedded()))
+}
+
+/// Counts lines straight from the file's bytes, splitting them exactly like
+/// [BufRead::lines]. Only applies while the content reads the same as decoded text: valid
+/// UTF-8 for UTF-8 files and plain ASCII for other ASCII compatible encodings. Returns
+/// `None` as soon as it does not, so that the file is decoded from the start instead.
+fn count_lines_fast(
+    fp: &mut File,
+    encoding: &'static Encoding,
+    line_processor: &mut dyn LineProcessor,
+) -> Result<Option<LineCount>, Error> {
+    let mut head = vec![];
+    (&mut *fp).take(3).read_to_end(&mut head)?;
+    // Bail out on a UTF-16 byte order mark, so that such files go through the decoder
+    if head.starts_with(&[0xFF, 0xFE]) || head.starts_with(&[0xFE, 0xFF]) {
+        return Ok(None);
+    }
+    let has_bom = head.starts_with(UTF8_BOM);
+    let utf8 = has_bom || encoding == UTF_8;
+
+    let mut count = LineCount::new();
+    let mut process_line = |line: &[u8]| -> Result<bool, Error> {
+        if !utf8 && !line.is_ascii() {
+            return Ok(false);
+        }
+        match std::str::from_utf8(line) {
+            Ok(text) => {
+                count += line_processor.process_line(text, encoding)?;
+                Ok(true)
+            }
+            Err(_) => Ok(false),
+        }
+    };
+
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut filled = 0;
+    if !has_bom {
+        buffer[..head.len()].copy_from_slice(&head);
+        filled = head.len();
+    }
+    loop {
+        // Grow the buffer for lines longer than it
+        if filled == buffer.len() {
+            buffer.resize(buffer.len() * 2, 0);
+        }
+        let read = fp.read(&mut buffer[filled..])?;
+        filled += read;
+
+        let mut start = 0;
+        for end in memchr_iter(b'\n', &buffer[..filled]) {
+            let line = &buffer[start..end];
+            if !process_line(line.strip_suffix(b"\r").unwrap_or(line))? {
+                return Ok(None);
+            }
+            start = end + 1;
+        }
+        if read == 0 {
+            if start < filled && !process_line(&buffer[start..filled])? {
+                return Ok(None);
+            }
+            break;
+        }
+        buffer.copy_within(start..filled, 0);
+        filled -= start;
+    }
+    Ok(Some(count))
+}
+
+/// Counts content held in memory, such as a git blob, the same way [count_lines] counts a file
+pub fn count_content(
+    content: &[u8],
+    encoding: Option<&'static Encoding>,
+    language: Option<&'static Language>,
+) -> Result<(LineCount, Vec<EmbeddedCount>), Error> {
+    let (text, used_encoding) = decode_content(content, encoding)?;
+    let mut line_processor = LineProcessorFactory::create(language);
+    let mut count = LineCount::new();
+    count.bytes = content.len() as u64;
+    for line in text.lines() {
+        count += line_processor.process_line(line, used_encoding)?;
+    }
+    count += line_processor.finish(used_encoding)?;
+    Ok((count, line_processor.embedded()))
+}
+
+/// Classifies every line of content held in memory, such as a git blob, returning a count of
+/// a single code, comment or blank line per line. Lines which can only be classified as part of
+/// the whole file, such as those of a notebook, are counted as code.
+pub fn classify_lines(
+    content: &[u8],
+    encoding: Option<&'static Encoding>,
+    language: Option<&'static Language>,
+) -> Result<Vec<LineCount>, Error> {
+    let (text, used_encoding) = decode_content(content, encoding)?;
+    let mut line_processor = LineProcessorFactory::create(language);
+    let mut lines = vec![];
+    for raw_line in text.split_inclusive('\n') {
+        let line = raw_line.strip_suffix('\n').unwrap_or(raw_line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let count = line_processor.process_line(line, used_encoding)?;
+        let mut line_count = LineCount {
+            lines: 1,
+            bytes: raw_line.len() as u64,
+            ..LineCount::new()
+        };
+        if count.lines > 0 && count.code_lines() == 0 {
+            if count.comment_lines > 0 {
+                line_count.comment_lines = 1;
+            } else {
+                line_count.blank_lines = 1;
+            }
+        }
+        lines.push(line_count);
+    }
+    Ok(lines)
+}
+
+/// Decodes content with the given encoding, or the detected one, unless a byte order mark
+/// overrides it
+fn decode_content<'a>(
+    content: &'a [u8],
+    encoding: Option<&'static Encoding>,
+) -> Result<(Cow<'a, str>, &'static Encoding), Error> {
+    let encoding = encoding.unwrap_or_else(|| detect_content_encoding(content).encoding);
+    let (text, used_encoding, had_errors) = encoding.decode(content);
+    if had_errors {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Line count failed whilst using encoding {used_encoding:?}"),
+        ));
+    }
+    Ok((text, used_encoding))
+}
+
 pub struct DetectedEncoding {
     pub encoding: &'static Encoding,
     pub confidence: f32,
@@ -143,15 +340,106 @@ pub fn detect_encoding(file: &Path) -> Result<DetectedEncoding, Error> {
     match OpenOptions::new().read(true).open(file) {
         Ok(fh) => {
             let mut reader: Vec<u8> = Vec::new();
-            let mut chunk = fh.take(8192);
+            let mut chunk = fh.take(DETECTION_SIZE as u64);
             let _read_result = chunk.read_to_end(&mut reader)?;
-
-            let result = detect(&reader);
-            Ok(DetectedEncoding {
-                encoding: Encoding::for_label(result.0.as_bytes()).unwrap_or(UTF_8),
-                confidence: result.1,
-            })
+            Ok(detect_content_encoding(&reader))
         }
         Err(err) => Err(err),
     }
 }
+
+/// Detects the encoding from the start of the content
+pub fn detect_content_encoding(content: &[u8]) -> DetectedEncoding {
+    let result = detect(&content[..content.len().min(DETECTION_SIZE)]);
+    DetectedEncoding {
+        encoding: Encoding::for_label(result.0.as_bytes()).unwrap_or(UTF_8),
+        confidence: result.1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::BUILTIN_LANGUAGES;
+    use encoding_rs::WINDOWS_1252;
+    use std::{env, fs};
+
+    /// Counts the content with the fast path, `None` when it does not apply, and by decoding it
+    fn count_both_ways(
+        name: &str,
+        content: &[u8],
+        encoding: &'static Encoding,
+    ) -> (Option<LineCount>, LineCount) {
+        let rust = BUILTIN_LANGUAGES
+            .iter()
+            .find(|language| language.name == "Rust");
+        let path = env::temp_dir().join(format!("lnav-{name}-{}.rs", std::process::id()));
+        fs::write(&path, content).unwrap();
+
+        let mut line_processor = LineProcessorFactory::create(rust);
+        let fast = count_lines_fast(
+            &mut File::open(&path).unwrap(),
+            encoding,
+            &mut *line_processor,
+        )
+        .unwrap()
+        .map(|count| count + line_processor.finish(encoding).unwrap());
+        let (decoded, _) = count_lines_decoded(File::open(&path).unwrap(), encoding, rust).unwrap();
+        fs::remove_file(&path).unwrap();
+        (fast, decoded)
+    }
+
+    fn lines(count: LineCount) -> (u64, u64, u64) {
+        (count.lines, count.comment_lines, count.blank_lines)
+    }
+
+    fn assert_same_counts(name: &str, content: &[u8], expected: (u64, u64, u64)) {
+        let (fast, decoded) = count_both_ways(name, content, UTF_8);
+        assert_eq!(lines(decoded), expected);
+        assert_eq!(fast.map(lines), Some(expected));
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        assert_same_counts("crlf", b"// one\r\n\r\nfn main() {}\r\n", (3, 1, 1));
+    }
+
+    #[test]
+    fn missing_trailing_newline() {
+        assert_same_counts("no-newline", b"fn main() {}\n\n// end", (3, 1, 1));
+        assert_same_counts("no-newline-crlf", b"fn main() {}\r\n\r", (2, 0, 1));
+    }
+
+    #[test]
+    fn utf8_byte_order_mark() {
+        assert_same_counts(
+            "bom",
+            b"\xEF\xBB\xBF// caf\xC3\xA9\nfn main() {}\n",
+            (2, 1, 0),
+        );
+    }
+
+    #[test]
+    fn utf8_byte_order_mark_overrides_the_encoding() {
+        let content = b"\xEF\xBB\xBF// caf\xC3\xA9\nfn main() {}\n";
+        let (fast, decoded) = count_both_ways("bom-latin1", content, WINDOWS_1252);
+        assert_eq!(lines(decoded), (2, 1, 0));
+        assert_eq!(fast.map(lines), Some((2, 1, 0)));
+    }
+
+    #[test]
+    fn lines_longer_than_a_chunk() {
+        let mut content = b"// ".to_vec();
+        content.extend(vec![b'a'; CHUNK_SIZE * 2 + 5]);
+        content.extend(b"\r\n\nfn main() {}\n");
+        assert_same_counts("long-line", &content, (3, 1, 1));
+    }
+
+    #[test]
+    fn latin1_falls_back_to_the_decoder() {
+        let content = b"// caf\xE9\n\nfn main() {}\n";
+        let (fast, decoded) = count_both_ways("latin1", content, WINDOWS_1252);
+        assert!(fast.is_none());
+        assert_eq!(lines(decoded), (3, 1, 1));
+    }
+}
@@ -1,20 +1,73 @@
+mod comment_line_processor;
+mod embedding_line_processor;
+mod lexer;
+mod notebook_line_processor;
 mod plain_line_processor;
 
-use crate::line_counter::LineCount;
+use crate::language::Language;
+use crate::line_counter::{EmbeddedCount, LineCount};
+use crate::line_processor::comment_line_processor::CommentLineProcessor;
+use crate::line_processor::embedding_line_processor::{EmbeddingLineProcessor, RegionSyntax};
+use crate::line_processor::notebook_line_processor::NotebookLineProcessor;
 use crate::line_processor::plain_line_processor::PlainLineProcessor;
 use encoding_rs::Encoding;
-use std::ffi::OsStr;
-use std::io::Error;
+use std::io::{Error, ErrorKind};
 
 pub struct LineProcessorFactory {}
 
 pub trait LineProcessor {
     fn process_line(&mut self, line: &str, encoding: &'static Encoding)
         -> Result<LineCount, Error>;
+
+    /// Counts lines which can only be classified once the whole file has been read
+    fn finish(&mut self, _encoding: &'static Encoding) -> Result<LineCount, Error> {
+        Ok(LineCount::new())
+    }
+
+    /// Line counts of regions written in other languages, already included in the file's count
+    fn embedded(&self) -> Vec<EmbeddedCount> {
+        vec![]
+    }
 }
 
 impl LineProcessorFactory {
-    pub fn create(_: &OsStr) -> Box<dyn LineProcessor> {
-        Box::new(PlainLineProcessor {})
+    pub fn create(language: Option<&'static Language>) -> Box<dyn LineProcessor> {
+        match language {
+            Some(language) => match language.name {
+                "Markdown" => Box::new(EmbeddingLineProcessor::new(
+                    language,
+                    RegionSyntax::CodeFences,
+                )),
+                "HTML" | "Vue" | "Svelte" => Box::new(EmbeddingLineProcessor::new(
+                    language,
+                    RegionSyntax::HtmlTags,
+                )),
+                "Jupyter Notebook" => Box::new(NotebookLineProcessor::new()),
+                _ => Box::new(CommentLineProcessor::new(language)),
+            },
+            None => Box::new(PlainLineProcessor {}),
+        }
+    }
+}
+
+/// Adds lines of an embedded language, merging them with earlier regions of the same language
+fn add_embedded(embedded: &mut Vec<EmbeddedCount>, language: &'static Language, count: LineCount) {
+    match embedded
+        .iter_mut()
+        .find(|e| e.language.name == language.name)
+    {
+        Some(existing) => existing.count += count,
+        None => embedded.push(EmbeddedCount { language, count }),
+    }
+}
+
+/// Fails if the line contains replacement characters, meaning it could not be decoded
+fn check_decoded(line: &str, encoding: &'static Encoding) -> Result<(), Error> {
+    if line.contains('\u{FFFD}') {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Line count failed whilst using encoding {encoding:?}"),
+        ));
     }
+    Ok(())
 }
@@ -0,0 +1,39 @@
+use crate::language::Language;
+use crate::line_counter::LineCount;
+use crate::line_processor::lexer::{Lexer, LineKind};
+use crate::line_processor::{check_decoded, LineProcessor};
+use encoding_rs::Encoding;
+use std::io::Error;
+
+/// Classifies lines as code, comment or blank using the syntax of a [Language]
+pub struct CommentLineProcessor {
+    lexer: Lexer,
+}
+
+impl CommentLineProcessor {
+    pub fn new(language: &'static Language) -> Self {
+        CommentLineProcessor {
+            lexer: Lexer::new(language),
+        }
+    }
+}
+
+impl LineProcessor for CommentLineProcessor {
+    fn process_line(
+        &mut self,
+        line: &str,
+        encoding: &'static Encoding,
+    ) -> Result<LineCount, Error> {
+        check_decoded(line, encoding)?;
+        let mut count = LineCount {
+            lines: 1,
+            ..LineCount::new()
+        };
+        match self.lexer.classify_line(line) {
+            LineKind::Code => {}
+            LineKind::Comment => count.comment_lines = 1,
+            LineKind::Blank => count.blank_lines = 1,
+        }
+        Ok(count)
+    }
+}
@@ -0,0 +1,249 @@
+use crate::language::Language;
+use crate::line_counter::{EmbeddedCount, LineCount};
+use crate::line_processor::comment_line_processor::CommentLineProcessor;
+use crate::line_processor::{add_embedded, check_decoded, LineProcessor, LineProcessorFactory};
+use encoding_rs::Encoding;
+use std::io::Error;
+
+/// How regions written in another language are delimited within the host language
+pub enum RegionSyntax {
+    /// Markdown fenced code blocks, such as ```` ```rust ````
+    CodeFences,
+    /// `<script>` and `<style>` elements of HTML and single-file components
+    HtmlTags,
+}
+
+enum RegionEnd {
+    Fence { fence_char: char, len: usize },
+    Tag(&'static str),
+}
+
+/// What the opening tag of an embedded element found in a line leads to
+enum OpenTag {
+    /// The element's content starts on the next line
+    Region(Option<&'static Language>, RegionEnd),
+    /// The opening tag goes on over the next line
+    Unfinished,
+}
+
+struct Region {
+    language: Option<&'static Language>,
+    processor: Box<dyn LineProcessor>,
+    end: RegionEnd,
+    count: LineCount,
+}
+
+/// Counts a host language while delegating embedded regions to child processors,
+/// keeping track of how many lines each embedded language contributed
+pub struct EmbeddingLineProcessor {
+    host: CommentLineProcessor,
+    syntax: RegionSyntax,
+    region: Option<Region>,
+    /// Lines of an opening tag read so far, when it spans several lines
+    unfinished_tag: Option<String>,
+    embedded: Vec<EmbeddedCount>,
+}
+
+impl EmbeddingLineProcessor {
+    pub fn new(language: &'static Language, syntax: RegionSyntax) -> Self {
+        EmbeddingLineProcessor {
+            host: CommentLineProcessor::new(language),
+            syntax,
+            region: None,
+            unfinished_tag: None,
+            embedded: vec![],
+        }
+    }
+
+    fn open_region(&mut self, line: &str) -> Option<Region> {
+        let (language, end) = match self.syntax {
+            RegionSyntax::CodeFences => open_fence(line)?,
+            RegionSyntax::HtmlTags => {
+                let text = match self.unfinished_tag.take() {
+                    Some(mut tag) => {
+                        tag.push('\n');
+                        tag.push_str(line);
+                        tag
+                    }
+                    None => line.to_string(),
+                };
+                match open_tag(&text)? {
+                    OpenTag::Region(language, end) => (language, end),
+                    OpenTag::Unfinished => {
+                        self.unfinished_tag = Some(text);
+                        return None;
+                    }
+                }
+            }
+        };
+        Some(Region {
+            language,
+            processor: LineProcessorFactory::create(language),
+            end,
+            count: LineCount::new(),
+        })
+    }
+
+    fn close_region(&mut self, encoding: &'static Encoding) -> Result<LineCount, Error> {
+        match self.region.take() {
+            Some(mut region) => {
+                let rest = region.processor.finish(encoding)?;
+                region.count += rest;
+                if let Some(language) = region.language {
+                    add_embedded(&mut self.embedded, language, region.count);
+                }
+                Ok(rest)
+            }
+            None => Ok(LineCount::new()),
+        }
+    }
+}
+
+impl LineProcessor for EmbeddingLineProcessor {
+    fn process_line(
+        &mut self,
+        line: &str,
+        encoding: &'static Encoding,
+    ) -> Result<LineCount, Error> {
+        check_decoded(line, encoding)?;
+        if let Some(region) = &mut self.region {
+            if !closes_region(&region.end, line) {
+                let count = region.processor.process_line(line, encoding)?;
+                region.count += count;
+                return Ok(count);
+            }
+            let rest = self.close_region(encoding)?;
+            return Ok(self.host.process_line(line, encoding)? + rest);
+        }
+        let count = self.host.process_line(line, encoding)?;
+        self.region = self.open_region(line);
+        Ok(count)
+    }
+
+    fn finish(&mut self, encoding: &'static Encoding) -> Result<LineCount, Error> {
+        self.close_region(encoding)
+    }
+
+    fn embedded(&self) -> Vec<EmbeddedCount> {
+        self.embedded.clone()
+    }
+}
+
+fn open_fence(line: &str) -> Option<(Option<&'static Language>, RegionEnd)> {
+    let trimmed = line.trim_start();
+    let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.chars().take_while(|c| *c == fence_char).count();
+    if len < 3 {
+        return None;
+    }
+    let info = trimmed[len..].trim();
+    if fence_char == '`' && info.contains('`') {
+        return None;
+    }
+    let language = info
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '{' || c == '}')
+        .find(|word| !word.is_empty())
+        .and_then(|word| Language::from_alias(word.trim_start_matches('.')));
+    Some((language, RegionEnd::Fence { fence_char, len }))
+}
+
+/// Finds a `<script>` or `<style>` element opened in the text, which holds the lines of the
+/// opening tag read so far
+fn open_tag(text: &str) -> Option<OpenTag> {
+    let lower = text.to_ascii_lowercase();
+    for (tag, end) in [("<script", "</script"), ("<style", "</style")] {
+        let Some(start) = lower.find(tag) else {
+            continue;
+        };
+        let after_tag = &lower[start + tag.len()..];
+        if after_tag.is_empty() {
+            return Some(OpenTag::Unfinished);
+        }
+        if !after_tag.starts_with(|c: char| c == '>' || c.is_whitespace()) {
+            continue;
+        }
+        let Some(tag_end) = after_tag.find('>') else {
+            return Some(OpenTag::Unfinished);
+        };
+        let attributes = &after_tag[..tag_end];
+        if after_tag.contains(end) {
+            return None;
+        }
+        let language = match (tag, attribute(attributes, "lang")) {
+            (_, Some(lang)) => Language::from_alias(lang),
+            ("<style", None) => Language::from_name("CSS"),
+            (_, None) => match attribute(attributes, "type") {
+                Some(t) if t.contains("typescript") => Language::from_name("TypeScript"),
+                Some(t) if !t.contains("javascript") && t != "module" => None,
+                _ => Language::from_name("JavaScript"),
+            },
+        };
+        return Some(OpenTag::Region(language, RegionEnd::Tag(end)));
+    }
+    None
+}
+
+/// Reads the value of an attribute of an already lower-cased tag
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let start = attributes.find(&format!("{name}="))? + name.len() + 1;
+    let value = &attributes[start..];
+    match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next(),
+        _ => value.split(|c: char| c.is_whitespace() || c == '/').next(),
+    }
+}
+
+fn closes_region(end: &RegionEnd, line: &str) -> bool {
+    match end {
+        RegionEnd::Fence { fence_char, len } => {
+            let trimmed = line.trim();
+            trimmed.chars().all(|c| c == *fence_char) && trimmed.chars().count() >= *len
+        }
+        RegionEnd::Tag(end) => line.to_ascii_lowercase().contains(end),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::UTF_8;
+
+    fn embedded_lines(lines: &[&str]) -> Vec<(&'static str, u64)> {
+        let html = Language::from_name("HTML").unwrap();
+        let mut processor = EmbeddingLineProcessor::new(html, RegionSyntax::HtmlTags);
+        for line in lines {
+            processor.process_line(line, UTF_8).unwrap();
+        }
+        processor.finish(UTF_8).unwrap();
+        processor
+            .embedded()
+            .iter()
+            .map(|embedded| (embedded.language.name, embedded.count.lines))
+            .collect()
+    }
+
+    #[test]
+    fn opening_tags_may_span_several_lines() {
+        let lines = [
+            "<html>",
+            "<script",
+            "  type=\"text/typescript\"",
+            "  defer>",
+            "let a = 1;",
+            "let b = 2;",
+            "</script>",
+            "<STYLE",
+            ">",
+            "p { color: red; }",
+            "</style>",
+            "</html>",
+        ];
+        assert_eq!(embedded_lines(&lines), [("TypeScript", 2), ("CSS", 1)]);
+    }
+
+    #[test]
+    fn elements_closed_on_the_tag_lines_are_not_regions() {
+        let lines = ["<script", "  src=\"app.js\"></script>", "<p>text</p>"];
+        assert_eq!(embedded_lines(&lines), []);
+    }
+}
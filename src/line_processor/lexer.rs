@@ -0,0 +1,294 @@
+use crate::language::Language;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineKind {
+    Code,
+    Comment,
+    Blank,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum LexerState {
+    Code,
+    BlockComment {
+        start: &'static str,
+        end: &'static str,
+        depth: u32,
+    },
+    String {
+        end: &'static str,
+        raw: bool,
+    },
+}
+
+enum Token {
+    LineComment,
+    BlockComment(&'static str, &'static str),
+    String(&'static str, bool),
+}
+
+/// Line by line lexer tracking block comments and string literals across lines,
+/// configured with the delimiters of a [Language]
+pub struct Lexer {
+    language: &'static Language,
+    state: LexerState,
+    /// First bytes of the language's comment and string delimiters
+    token_starts: [bool; 256],
+}
+
+impl Lexer {
+    pub fn new(language: &'static Language) -> Self {
+        let mut token_starts = [false; 256];
+        let delimiters = language
+            .line_comments
+            .iter()
+            .chain(language.block_comments.iter().map(|(start, _)| start))
+            .chain(language.strings.iter().map(|(start, _)| start))
+            .chain(language.raw_strings.iter().map(|(start, _)| start));
+        for delimiter in delimiters {
+            if let Some(&first) = delimiter.as_bytes().first() {
+                token_starts[first as usize] = true;
+            }
+        }
+        Lexer {
+            language,
+            state: LexerState::Code,
+            token_starts,
+        }
+    }
+
+    /// Finds the longest token starting at the beginning of `rest`,
+    /// preferring comments over strings of the same length
+    fn match_token(&self, rest: &str) -> Option<(Token, usize)> {
+        let language = self.language;
+        let mut best: Option<(Token, usize)> = None;
+        let mut consider = |token: Token, start: &str| {
+            if rest.starts_with(start) && best.as_ref().is_none_or(|b| start.len() > b.1) {
+                best = Some((token, start.len()));
+            }
+        };
+        for (start, end) in language.block_comments {
+            consider(Token::BlockComment(start, end), start);
+        }
+        for start in language.line_comments {
+            consider(Token::LineComment, start);
+        }
+        for (start, end) in language.raw_strings {
+            consider(Token::String(end, true), start);
+        }
+        for (start, end) in language.strings {
+            consider(Token::String(end, false), start);
+        }
+        best
+    }
+
+    /// Advances the lexer over a single line and classifies it
+    pub fn classify_line(&mut self, line: &str) -> LineKind {
+        let mut has_code = false;
+        let mut has_comment = false;
+        let mut pos = 0;
+
+        while pos < line.len() {
+            let rest = &line[pos..];
+            let next_char = rest.chars().next().unwrap();
+            match self.state {
+                LexerState::Code => {
+                    if next_char.is_whitespace() {
+                        pos += next_char.len_utf8();
+                        continue;
+                    }
+                    if next_char == '\'' && self.language.char_literals {
+                        has_code = true;
+                        pos += char_literal_len(rest).unwrap_or(1);
+                        continue;
+                    }
+                    if !self.token_starts[rest.as_bytes()[0] as usize] {
+                        has_code = true;
+                        pos += next_char.len_utf8();
+                        continue;
+                    }
+                    match self.match_token(rest) {
+                        Some((Token::LineComment, _)) => {
+                            has_comment = true;
+                            break;
+                        }
+                        Some((Token::BlockComment(start, end), len)) => {
+                            has_comment = true;
+                            self.state = LexerState::BlockComment {
+                                start,
+                                end,
+                                depth: 1,
+                            };
+                            pos += len;
+                        }
+                        Some((Token::String(end, raw), len)) => {
+                            has_code = true;
+                            self.state = LexerState::String { end, raw };
+                            pos += len;
+                        }
+                        None => {
+                            has_code = true;
+                            pos += next_char.len_utf8();
+                        }
+                    }
+                }
+                LexerState::BlockComment { start, end, depth } => {
+                    if !next_char.is_whitespace() {
+                        has_comment = true;
+                    }
+                    if rest.starts_with(end) {
+                        self.state = if depth == 1 {
+                            LexerState::Code
+                        } else {
+                            LexerState::BlockComment {
+                                start,
+                                end,
+                                depth: depth - 1,
+                            }
+                        };
+                        pos += end.len();
+                    } else if self.language.nested_comments && rest.starts_with(start) {
+                        self.state = LexerState::BlockComment {
+                            start,
+                            end,
+                            depth: depth + 1,
+                        };
+                        pos += start.len();
+                    } else {
+                        pos += next_char.len_utf8();
+                    }
+                }
+                LexerState::String { end, raw } => {
+                    if !next_char.is_whitespace() {
+                        has_code = true;
+                    }
+                    if !raw && next_char == '\\' {
+                        pos += next_char.len_utf8();
+                        if let Some(escaped) = line[pos..].chars().next() {
+                            pos += escaped.len_utf8();
+                        }
+                    } else if rest.starts_with(end) {
+                        self.state = LexerState::Code;
+                        pos += end.len();
+                    } else {
+                        pos += next_char.len_utf8();
+                    }
+                }
+            }
+        }
+
+        if has_code {
+            LineKind::Code
+        } else if has_comment {
+            LineKind::Comment
+        } else {
+            LineKind::Blank
+        }
+    }
+}
+
+/// Length of the character literal at the start of `rest`, such as `'"'` or `'\n'`, or `None`
+/// when the quote does not start one, as with the lifetime in `&'a str`
+fn char_literal_len(rest: &str) -> Option<usize> {
+    let mut chars = rest.char_indices().skip(1);
+    match chars.next()? {
+        // The longest escape is `\u{10FFFF}`
+        (_, '\\') => chars
+            .skip(1)
+            .take(9)
+            .find(|&(_, c)| c == '\'')
+            .map(|(index, _)| index + 1),
+        (_, '\'') => None,
+        _ => match chars.next()? {
+            (index, '\'') => Some(index + 1),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::BUILTIN_LANGUAGES;
+    use LineKind::{Blank, Code, Comment};
+
+    fn classify_rust(lines: &[&str]) -> Vec<LineKind> {
+        let rust = BUILTIN_LANGUAGES
+            .iter()
+            .find(|language| language.name == "Rust")
+            .unwrap();
+        let mut lexer = Lexer::new(rust);
+        lines.iter().map(|line| lexer.classify_line(line)).collect()
+    }
+
+    #[test]
+    fn char_literals_do_not_open_strings() {
+        let lines = [
+            "let c = '\"';",
+            "// comment",
+            "let b = b'\"';",
+            "// comment",
+            "let q = ['\\'', '\\\\', '\\u{1F600}', '\"'];",
+            "// comment",
+        ];
+        assert_eq!(
+            classify_rust(&lines),
+            [Code, Comment, Code, Comment, Code, Comment]
+        );
+    }
+
+    #[test]
+    fn lifetimes_are_not_char_literals() {
+        let lines = [
+            "fn first<'a>(s: &'a str) -> &'a str {",
+            "    // comment",
+            "    let s: &'static str = \"'\";",
+            "// comment",
+            "struct Wrapper<'a>(&'a [u8]);",
+            "// comment",
+        ];
+        assert_eq!(
+            classify_rust(&lines),
+            [Code, Comment, Code, Comment, Code, Comment]
+        );
+    }
+
+    #[test]
+    fn strings_hide_comment_delimiters() {
+        let lines = [
+            "let url = \"https://example.com\";",
+            "let escaped = \"\\\"// still a string\";",
+            "let multiline = \"first",
+            "// second line of the string\";",
+            "",
+            "// comment",
+        ];
+        assert_eq!(
+            classify_rust(&lines),
+            [Code, Code, Code, Code, Blank, Comment]
+        );
+    }
+
+    #[test]
+    fn raw_strings_end_at_their_own_delimiter() {
+        let lines = [
+            "let raw = r#\"a \"quoted\" // word\"#;",
+            "// comment",
+            "let raw = r#\"",
+            "/* not a comment \\\"#;",
+            "/* comment */",
+        ];
+        assert_eq!(classify_rust(&lines), [Code, Comment, Code, Code, Comment]);
+    }
+
+    #[test]
+    fn block_comments_nest() {
+        let lines = [
+            "/* outer /* inner */",
+            "still in the outer comment */",
+            "code();",
+            "/* a */ code();",
+        ];
+        assert_eq!(classify_rust(&lines), [Comment, Comment, Code, Code]);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::language::Language;
+use crate::line_counter::{EmbeddedCount, LineCount};
+use crate::line_processor::{add_embedded, check_decoded, LineProcessor, LineProcessorFactory};
+use encoding_rs::Encoding;
+use serde_json::Value;
+use std::io::{Error, ErrorKind};
+
+/// Counts the cell sources of a Jupyter notebook instead of its raw JSON lines
+pub struct NotebookLineProcessor {
+    source: String,
+    embedded: Vec<EmbeddedCount>,
+}
+
+impl NotebookLineProcessor {
+    pub fn new() -> Self {
+        NotebookLineProcessor {
+            source: String::new(),
+            embedded: vec![],
+        }
+    }
+}
+
+/// Joins a cell source, which is stored either as a string or as a list of lines
+fn cell_source(cell: &Value) -> String {
+    match &cell["source"] {
+        Value::String(source) => source.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+impl LineProcessor for NotebookLineProcessor {
+    fn process_line(
+        &mut self,
+        line: &str,
+        encoding: &'static Encoding,
+    ) -> Result<LineCount, Error> {
+        check_decoded(line, encoding)?;
+        self.source.push_str(line);
+        self.source.push('\n');
+        Ok(LineCount::new())
+    }
+
+    fn finish(&mut self, encoding: &'static Encoding) -> Result<LineCount, Error> {
+        let mut count = LineCount::new();
+        if self.source.trim().is_empty() {
+            return Ok(count);
+        }
+        let notebook: Value = serde_json::from_str(&self.source)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        let kernel_language = notebook["metadata"]["language_info"]["name"]
+            .as_str()
+            .or(notebook["metadata"]["kernelspec"]["language"].as_str())
+            .unwrap_or("python");
+
+        for cell in notebook["cells"].as_array().into_iter().flatten() {
+            let language = match cell["cell_type"].as_str() {
+                Some("code") => Language::from_alias(kernel_language),
+                Some("markdown") => Language::from_name("Markdown"),
+                _ => None,
+            };
+            let mut processor = LineProcessorFactory::create(language);
+            let mut cell_count = LineCount::new();
+            for line in cell_source(cell).lines() {
+                cell_count += processor.process_line(line, encoding)?;
+            }
+            cell_count += processor.finish(encoding)?;
+            if let Some(language) = language {
+                add_embedded(&mut self.embedded, language, cell_count);
+            }
+            for embedded in processor.embedded() {
+                add_embedded(&mut self.embedded, embedded.language, embedded.count);
+            }
+            count += cell_count;
+        }
+        Ok(count)
+    }
+
+    fn embedded(&self) -> Vec<EmbeddedCount> {
+        self.embedded.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::UTF_8;
+
+    #[test]
+    fn keeps_code_blocks_of_markdown_cells() {
+        let notebook = r##"{
+            "metadata": {"language_info": {"name": "python"}},
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Title\n", "```rust\n", "let a = 1;\n", "```\n"]},
+                {"cell_type": "code", "source": "print(1)\nprint(2)\n"}
+            ]
+        }"##;
+        let mut processor = NotebookLineProcessor::new();
+        for line in notebook.lines() {
+            processor.process_line(line, UTF_8).unwrap();
+        }
+        assert_eq!(processor.finish(UTF_8).unwrap().lines, 6);
+        let embedded: Vec<_> = processor
+            .embedded()
+            .iter()
+            .map(|embedded| (embedded.language.name, embedded.count.lines))
+            .collect();
+        assert_eq!(embedded, [("Markdown", 4), ("Rust", 1), ("Python", 2)]);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::line_counter::LineCount;
-use crate::line_processor::LineProcessor;
+use crate::line_processor::{check_decoded, LineProcessor};
 use encoding_rs::Encoding;
-use std::io::{Error, ErrorKind};
+use std::io::Error;
 
 pub struct PlainLineProcessor {}
 
@@ -11,22 +11,19 @@ impl LineProcessor for PlainLineProcessor {
         line: &str,
         encoding: &'static Encoding,
     ) -> Result<LineCount, Error> {
-        if line.contains('\u{FFFD}') {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("Line count failed whilst using encoding {encoding:?}"),
-            ));
-        }
+        check_decoded(line, encoding)?;
         if line.chars().all(char::is_whitespace) {
             Ok(LineCount {
                 lines: 1,
                 blank_lines: 1,
+                comment_lines: 0,
                 bytes: 0,
             })
         } else {
             Ok(LineCount {
                 lines: 1,
                 blank_lines: 0,
+                comment_lines: 0,
                 bytes: 0,
             })
         }
@@ -1,36 +1,70 @@
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::ffi::OsString;
+use std::io::{stdout, Write};
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 use std::{fs, process};
 
-use clap::{ArgAction, Parser};
+use clap::{ArgAction, Parser, Subcommand};
 use encoding_rs::Encoding;
+use git2::Repository;
 
+use crate::counter_walker::counted_files::CountedFiles;
+use crate::counter_walker::git_files::git_files;
+use crate::counter_walker::ignore_rules::IgnoreRules;
+use crate::counter_walker::path_patterns::PathPatterns;
+use crate::counter_walker::walk_errors::WalkErrors;
 use crate::counter_walker::walk_path_result::WalkPathResult;
 use crate::counter_walker::{handle_file_entry, simple_walk_path, walk_path, ExcludeOptions};
+use crate::git_diff::diff_report::{print_diff_json, print_diff_text};
+use crate::git_diff::diff_revisions;
+use crate::git_history::history_report::{
+    print_history_csv, print_history_json, print_history_text,
+};
+use crate::git_history::{walk_history, HistorySampling};
+use crate::language::definitions::{default_definition_paths, load_definitions};
+use crate::language::{init_languages, Language};
 use crate::line_counter::LineCountFormat;
+use crate::result_printer::cloc_result_printer::{ClocFormat, ClocResultPrinter};
+use crate::result_printer::csv_result_printer::CsvResultPrinter;
 #[cfg(debug_assertions)]
 use crate::result_printer::debug_result_printer::DebugResultPrinter;
+use crate::result_printer::json_result_printer::JsonResultPrinter;
 use crate::result_printer::noop_result_printer::NoopResultPrinter;
 use crate::result_printer::simple_result_printer::SimpleResultPrinter;
+use crate::result_printer::tokei_result_printer::TokeiResultPrinter;
 use crate::result_printer::verbose_result_printer::VerboseResultPrinter;
-use crate::result_printer::{FinalDisplayOptions, PrinterEntry, ResultPrinter};
-use crate::summarizer::default_summarizer::DefaultSummarizer;
-use crate::summarizer::leaderboard_summarizer::LeaderboardSummarizer;
+use crate::result_printer::{FinalDisplayOptions, OutputFormat, ResultPrinter};
+use crate::summarizer::blame_summarizer::BlameSummarizer;
+use crate::summarizer::composite_summarizer::CompositeSummarizer;
+use crate::summarizer::default_summarizer::{DefaultSummarizer, SummaryGrouping};
+use crate::summarizer::leaderboard_summarizer::{
+    LeaderboardSort, LeaderboardSummarizer, SortOrder,
+};
 use crate::summarizer::noop_summarizer::NoopSummarizer;
+use crate::summarizer::owners_summarizer::OwnersSummarizer;
 use crate::summarizer::Summarizer;
 
 mod counter_walker;
+mod git_diff;
+mod git_history;
+mod language;
 mod line_counter;
 mod line_processor;
 mod result_printer;
 mod summarizer;
 
+/// Exit code when `--strict` stops the count at an unreadable entry, distinct from the `1` of
+/// other errors and the `2` of command line usage errors
+const STRICT_EXIT_CODE: i32 = 3;
+
 #[derive(Debug, Parser)]
 #[command(name = "CMDStore")]
 /// Project line counter utility
 struct LineNavArgs {
-    #[arg(long, short, action = ArgAction::Count)]
+    #[command(subcommand)]
+    command: Option<LineNavCommand>,
+    #[arg(long, short, action = ArgAction::Count, global = true)]
     /// Shows a tree with all the files
     /// Add a second flag to enable --very-verbose output
     verbose: u8,
@@ -46,43 +80,172 @@ struct LineNavArgs {
     #[clap(long, short, action)]
     #[cfg(debug_assertions)]
     debug: bool,
-    #[clap(long, short, default_value = "UTF-8")]
+    #[clap(long, short, default_value = "UTF-8", global = true)]
     /// Encoding to read files with. Set "auto" to automatically detect
     encoding: String,
-    #[clap(long, short = 'f', required = false, value_delimiter = ',')]
+    #[clap(
+        long,
+        short = 'f',
+        required = false,
+        value_delimiter = ',',
+        global = true
+    )]
     /// File extensions to count through
-    file_extensions: Vec<String>,
+    file_extensions: Vec<OsString>,
     #[clap(num_args = 0.., default_values = ["."])]
     /// Folders to count
-    paths: Vec<String>,
-    #[clap(long, short = 'x', num_args = 1.., required = false)]
+    paths: Vec<PathBuf>,
+    #[clap(long, short = 'x', num_args = 1.., required = false, global = true)]
     /// Excluded file names
-    exclude: Vec<String>,
+    exclude: Vec<OsString>,
+    #[clap(long, num_args = 1.., required = false, global = true)]
+    /// Only count files whose path relative to the counted folder matches one of the globs
+    include_glob: Vec<String>,
+    #[clap(long, num_args = 1.., required = false, global = true)]
+    /// Skip files and folders whose path relative to the counted folder matches one of the globs
+    exclude_glob: Vec<String>,
+    #[clap(long, num_args = 1.., required = false, global = true)]
+    /// Only count files whose path relative to the counted folder matches one of the regexes
+    include_regex: Vec<String>,
+    #[clap(long, num_args = 1.., required = false, global = true)]
+    /// Skip files and folders whose path relative to the counted folder matches one of the regexes
+    exclude_regex: Vec<String>,
     #[clap(long, short = 'm', default_missing_value = "0", num_args = 0..)]
     /// Summarize line counts by file extension
     summary: Option<u32>,
-    /// Display a leaderboard of longest files
-    #[clap(long, short, action)]
-    leaderboard: bool,
+    #[clap(long, value_name = "extension|language|dir[:depth]", global = true)]
+    /// Group the summary by file extension, detected language or directory down to a depth
+    summary_by: Option<SummaryGrouping>,
+    #[clap(long, action)]
+    /// List the extensions making up each row of a summary grouped by language
+    summary_expand: bool,
+    #[clap(long, value_name = "depth", num_args = 0..=1, default_missing_value = "1",
+        require_equals = true, value_parser = clap::value_parser!(u64).range(1..))]
+    /// Summarize line counts by directory down to a depth, given as `--by-dir=n`, alongside
+    /// any other summary
+    by_dir: Option<u64>,
+    #[clap(long, action)]
+    /// Summarize line counts by the author of the last change to each line, using git blame
+    by_author: bool,
+    #[clap(long, action)]
+    /// Summarize line counts by the owners assigned in the repository's CODEOWNERS file
+    by_owner: bool,
+    #[clap(
+        long,
+        short,
+        value_name = "limit",
+        default_missing_value = "0",
+        num_args = 0..=1,
+        require_equals = true
+    )]
+    /// Display a leaderboard of the longest files, limited to the top n when given as `-l=n` or
+    /// `--leaderboard=n`. The `=` is required: in `--leaderboard 20`, `20` is a path to count.
+    leaderboard: Option<u32>,
+    #[clap(long, value_enum, default_value_t = LeaderboardSort::Lines)]
+    /// What the leaderboard ranks files by
+    leaderboard_sort: LeaderboardSort,
+    #[clap(long, value_enum, default_value_t = SortOrder::Desc)]
+    /// Rank the leaderboard from the highest or the lowest value
+    leaderboard_order: SortOrder,
+    #[clap(long, action)]
+    /// Rank the files of each language separately in the leaderboard
+    leaderboard_by_language: bool,
+    #[clap(long, value_enum, default_value_t = OutputFormat::Text, global = true)]
+    /// Format of the report
+    output: OutputFormat,
+    #[clap(long, global = true)]
+    /// Additional language definitions file
+    languages: Option<PathBuf>,
+    #[clap(long, action)]
+    /// Count files ignored by .gitignore, .ignore, .lnavignore and git's exclude files
+    no_ignore: bool,
+    #[clap(long, short = 'j', default_value_t = 0)]
+    /// Number of threads counting files, 0 uses one per CPU core
+    threads: usize,
+    #[clap(long, action)]
+    /// Stop at the first file or folder which cannot be read instead of skipping it
+    strict: bool,
+    #[clap(long, short = 'L', action)]
+    /// Walk into symlinked folders and count symlinked files
+    follow_symlinks: bool,
+    #[clap(long, action)]
+    /// Do not walk into folders on other file systems
+    one_file_system: bool,
+    #[clap(long, action)]
+    /// Only count files tracked by git, read from the repository's index
+    git_tracked: bool,
+    #[clap(long, action, requires = "git_tracked")]
+    /// Also count untracked files which git does not ignore
+    include_untracked: bool,
+    #[clap(long, value_name = "revision")]
+    /// Count the lines changed since the revision, like `diff <revision> HEAD`
+    since: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+enum LineNavCommand {
+    /// Count the lines added and removed between two commits of the git repository
+    Diff {
+        /// Revision to compare from
+        from: String,
+        /// Revision to compare to
+        #[clap(default_value = "HEAD")]
+        to: String,
+        #[clap(last = true)]
+        /// Folders to count changes in
+        paths: Vec<PathBuf>,
+    },
+    /// Count the lines of each commit along the first-parent history of the git repository
+    History {
+        /// Newest revision counted
+        #[clap(default_value = "HEAD")]
+        revision: String,
+        #[clap(long, value_enum, default_value_t = HistorySampling::Commit)]
+        /// Which commits to count
+        sample: HistorySampling,
+        #[clap(last = true)]
+        /// Folders to count lines in
+        paths: Vec<PathBuf>,
+    },
 }
 
 fn main() {
     let args = LineNavArgs::parse();
-    let include_extensions: HashSet<String> =
+    let include_extensions: HashSet<OsString> =
         args.file_extensions.iter().map(|x| x.to_owned()).collect();
-    let exclude: HashSet<String> = args.exclude.iter().map(|x| x.to_owned()).collect();
-    let paths: Vec<PathBuf> = args
-        .paths
-        .iter()
-        .map(fs::canonicalize)
-        .map(|x| match x {
-            Ok(path) => path,
-            Err(err) => {
-                eprintln!("Invalid path. {err:?}");
-                process::exit(1);
-            }
-        })
-        .collect();
+    let exclude: HashSet<OsString> = args.exclude.iter().map(|x| x.to_owned()).collect();
+    let patterns = match PathPatterns::new(
+        &args.include_glob,
+        &args.exclude_glob,
+        &args.include_regex,
+        &args.exclude_regex,
+    ) {
+        Ok(patterns) => patterns,
+        Err(err) => {
+            eprintln!("Invalid pattern. {err}");
+            process::exit(1);
+        }
+    };
+    let paths = canonical_paths(&args.paths);
+
+    let mut user_languages = vec![];
+    for definitions_path in default_definition_paths() {
+        if definitions_path.is_file() {
+            user_languages.extend(load_language_definitions(&definitions_path));
+        }
+    }
+    if let Some(definitions_path) = &args.languages {
+        user_languages.extend(load_language_definitions(definitions_path));
+    }
+    init_languages(user_languages);
+
+    if let Err(err) = rayon::ThreadPoolBuilder::new()
+        .num_threads(args.threads)
+        .build_global()
+    {
+        eprintln!("Could not start the thread pool. {err}");
+        process::exit(1);
+    }
 
     #[cfg(target_os = "windows")]
     {
@@ -100,6 +263,58 @@ fn main() {
         Some(encoding.unwrap())
     };
 
+    let exclude_options = |root| ExcludeOptions {
+        root,
+        include_extensions: &include_extensions,
+        exclude: &exclude,
+        patterns: &patterns,
+        ignore: None,
+        follow_symlinks: false,
+        one_file_system: false,
+        listed_files: None,
+    };
+    let revision_paths = |revision_paths: &[PathBuf]| {
+        if revision_paths.is_empty() {
+            paths.clone()
+        } else {
+            canonical_paths(revision_paths)
+        }
+    };
+    match &args.command {
+        Some(LineNavCommand::Diff {
+            from,
+            to,
+            paths: diff_paths,
+        }) => {
+            let diff_paths = revision_paths(diff_paths);
+            count_diff(&args, from, to, &diff_paths, encoding, exclude_options);
+            return;
+        }
+        Some(LineNavCommand::History {
+            revision,
+            sample,
+            paths: history_paths,
+        }) => {
+            let history_paths = revision_paths(history_paths);
+            count_history(
+                &args,
+                revision,
+                *sample,
+                &history_paths,
+                encoding,
+                exclude_options,
+            );
+            return;
+        }
+        None => {
+            if let Some(since) = &args.since {
+                let diff_paths = revision_paths(&args.paths);
+                count_diff(&args, since, "HEAD", &diff_paths, encoding, exclude_options);
+                return;
+            }
+        }
+    }
+
     let display_options = FinalDisplayOptions {
         show_all: args.all_files,
         verbose: args.verbose > 0,
@@ -112,37 +327,110 @@ fn main() {
         },
     };
 
-    let mut printer_impl: Box<dyn ResultPrinter> =
-        if display_options.verbose && display_options.simple {
-            Box::new(SimpleResultPrinter::new())
-        } else if display_options.verbose {
-            Box::new(VerboseResultPrinter::new())
+    let mut printer_impl: Box<dyn ResultPrinter> = if args.output == OutputFormat::Json {
+        Box::new(JsonResultPrinter::new())
+    } else if args.output == OutputFormat::Csv {
+        Box::new(CsvResultPrinter::new(b','))
+    } else if args.output == OutputFormat::Tsv {
+        Box::new(CsvResultPrinter::new(b'\t'))
+    } else if args.output == OutputFormat::ClocYaml {
+        Box::new(ClocResultPrinter::new(ClocFormat::Yaml))
+    } else if args.output == OutputFormat::ClocXml {
+        Box::new(ClocResultPrinter::new(ClocFormat::Xml))
+    } else if args.output == OutputFormat::TokeiJson {
+        Box::new(TokeiResultPrinter::new())
+    } else if display_options.verbose && display_options.simple {
+        Box::new(SimpleResultPrinter::new())
+    } else if display_options.verbose {
+        Box::new(VerboseResultPrinter::new())
+    } else {
+        #[cfg(debug_assertions)]
+        if args.debug {
+            Box::new(DebugResultPrinter {})
         } else {
-            #[cfg(debug_assertions)]
-            if args.debug {
-                Box::new(DebugResultPrinter {})
-            } else {
-                Box::new(NoopResultPrinter {})
-            }
-            #[cfg(not(debug_assertions))]
-            Box::new(SimpleResultPrinter::new())
-        };
+            Box::new(NoopResultPrinter {})
+        }
+        #[cfg(not(debug_assertions))]
+        Box::new(SimpleResultPrinter::new())
+    };
 
     (*printer_impl).set_options(&display_options);
 
-    let mut summarizer: Box<dyn Summarizer> = if args.summary.is_some() {
-        Box::new(DefaultSummarizer::new())
-    } else if args.leaderboard {
-        Box::new(LeaderboardSummarizer::new())
-    } else {
-        Box::new(NoopSummarizer::new())
+    let summary_limit = match (args.summary, args.summary_by) {
+        (None, Some(_)) => Some(0),
+        (limit, _) => limit,
+    };
+
+    // Every selected summary is reported, from a single walk
+    let mut summarizers: Vec<Box<dyn Summarizer>> = vec![];
+    if summary_limit.is_some() {
+        let mut summarizer = DefaultSummarizer::new(
+            args.summary_by.unwrap_or(SummaryGrouping::Extension),
+            args.summary_expand,
+        );
+        summarizer.set_roots(&paths);
+        summarizers.push(Box::new(summarizer));
+    }
+    if let Some(depth) = args.by_dir {
+        let mut summarizer =
+            DefaultSummarizer::new(SummaryGrouping::Directory(depth as usize), false);
+        summarizer.set_roots(&paths);
+        summarizers.push(Box::new(summarizer));
+    }
+    if args.by_author {
+        summarizers.push(Box::new(BlameSummarizer::new(encoding)));
+    }
+    if args.by_owner {
+        summarizers.push(Box::new(OwnersSummarizer::new()));
+    }
+    if let Some(limit) = summary_limit {
+        for summarizer in &mut summarizers {
+            summarizer.set_limit(limit);
+        }
+    }
+    if let Some(limit) = args.leaderboard {
+        let mut summarizer = LeaderboardSummarizer::new(
+            args.leaderboard_sort,
+            args.leaderboard_order,
+            args.leaderboard_by_language,
+        );
+        summarizer.set_roots(&paths);
+        summarizer.set_limit(limit);
+        summarizers.push(Box::new(summarizer));
+    }
+    let mut summarizer: Box<dyn Summarizer> = match summarizers.len() {
+        0 => Box::new(NoopSummarizer::new()),
+        1 => summarizers.pop().unwrap(),
+        _ => Box::new(CompositeSummarizer::new(summarizers)),
     };
 
     let mut final_res = WalkPathResult::new();
+    let mut walk_errors = WalkErrors::new(args.strict);
+    let mut counted = CountedFiles::new();
     let start = Instant::now();
 
     for path in paths.iter() {
         if path.is_dir() {
+            // git has already applied the ignore files to the files it lists, and force-added
+            // files are counted like any other tracked file
+            let ignore_rules =
+                (!args.no_ignore && !args.git_tracked).then(|| IgnoreRules::new(path));
+            let listed_files = args.git_tracked.then(|| {
+                git_files(path, args.include_untracked).unwrap_or_else(|err| {
+                    eprintln!("Could not read the git index. {}", err.message());
+                    process::exit(1);
+                })
+            });
+            let exclude_options = ExcludeOptions {
+                root: path,
+                include_extensions: &include_extensions,
+                exclude: &exclude,
+                patterns: &patterns,
+                ignore: ignore_rules.as_ref(),
+                follow_symlinks: args.follow_symlinks,
+                one_file_system: args.one_file_system,
+                listed_files: listed_files.as_deref(),
+            };
             let sub_count = if printer_impl.requires_advanced_walker() {
                 walk_path(
                     path,
@@ -150,42 +438,46 @@ fn main() {
                     0,
                     &*printer_impl,
                     &mut *summarizer,
-                    &ExcludeOptions {
-                        include_extensions: &include_extensions,
-                        exclude: &exclude,
-                    },
+                    &exclude_options,
+                    &mut walk_errors,
+                    &mut counted,
                 )
-                .expect("Count failed")
             } else {
                 simple_walk_path(
                     path,
                     encoding,
                     &*printer_impl,
                     &mut *summarizer,
-                    &ExcludeOptions {
-                        include_extensions: &include_extensions,
-                        exclude: &exclude,
-                    },
+                    &exclude_options,
+                    &mut walk_errors,
+                    &mut counted,
                 )
-                .expect("Count failed")
-            };
+            }
+            .unwrap_or_else(|err| stop_count(err, &*printer_impl));
             if paths.len() > 1 {
                 printer_impl.print_subtotal(sub_count.line_count);
             }
             final_res += sub_count;
         } else if path.is_file() {
             let res = &mut WalkPathResult::new();
+            printer_impl.print_header(path, 1);
+            // Only machine readable reports list files given directly as arguments
+            let file_printer: &dyn ResultPrinter = if args.output == OutputFormat::Text {
+                &NoopResultPrinter {}
+            } else {
+                &*printer_impl
+            };
             handle_file_entry(
                 encoding,
                 path,
-                &PrinterEntry::from_path(path),
                 0,
                 res,
-                &NoopResultPrinter {},
+                file_printer,
                 &mut *summarizer,
+                &mut walk_errors,
+                &mut counted,
             )
-            .expect("Count failed");
-            printer_impl.print_header(path, 1);
+            .unwrap_or_else(|err| stop_count(err, &*printer_impl));
             printer_impl.print_subtotal(res.line_count);
             final_res += *res;
         }
@@ -193,12 +485,156 @@ fn main() {
 
     let duration = start.elapsed();
 
-    if args.summary.is_some() {
-        summarizer.set_limit(args.summary.unwrap());
-        summarizer.print_summary(final_res.line_count);
-    } else if args.leaderboard {
-        summarizer.print_summary(final_res.line_count);
-    }
+    printer_impl.print_summary(&mut *summarizer, final_res.line_count);
 
     printer_impl.print_result(final_res, &duration);
+
+    if !walk_errors.errors.is_empty() {
+        eprintln!(
+            "{} entr{} could not be read:",
+            walk_errors.errors.len(),
+            if walk_errors.errors.len() == 1 {
+                "y"
+            } else {
+                "ies"
+            }
+        );
+        for walk_error in &walk_errors.errors {
+            eprintln!("  {}: {}", walk_error.path.display(), walk_error.error);
+        }
+    }
+}
+
+fn canonical_paths(paths: &[PathBuf]) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .map(fs::canonicalize)
+        .map(|x| match x {
+            Ok(path) => path,
+            Err(err) => {
+                eprintln!("Invalid path. {err:?}");
+                process::exit(1);
+            }
+        })
+        .collect()
+}
+
+/// Reports the lines changed between two revisions of the repository containing the paths,
+/// in the files below any of the paths which the filters select
+fn count_diff<'a>(
+    args: &LineNavArgs,
+    from: &str,
+    to: &str,
+    paths: &'a [PathBuf],
+    encoding: Option<&'static Encoding>,
+    exclude_options: impl Fn(&'a Path) -> ExcludeOptions<'a>,
+) {
+    if args.output != OutputFormat::Text && args.output != OutputFormat::Json {
+        eprintln!("Changes can only be reported as text or json");
+        process::exit(1);
+    }
+    let diff = open_repository(paths).and_then(|(repository, workdir)| {
+        diff_revisions(&repository, from, to, encoding, |relative| {
+            is_counted_below(&workdir.join(relative), paths, &exclude_options)
+        })
+    });
+    let diff = diff.unwrap_or_else(|err| {
+        eprintln!("Could not compare the revisions. {}", err.message());
+        process::exit(1);
+    });
+    let grouping = args.summary_by.unwrap_or(SummaryGrouping::Language);
+    if args.output == OutputFormat::Json {
+        print_diff_json(&diff, grouping);
+    } else {
+        print_diff_text(&diff, grouping, args.verbose > 0);
+    }
+}
+
+fn count_history<'a>(
+    args: &LineNavArgs,
+    revision: &str,
+    sampling: HistorySampling,
+    paths: &'a [PathBuf],
+    encoding: Option<&'static Encoding>,
+    exclude_options: impl Fn(&'a Path) -> ExcludeOptions<'a>,
+) {
+    let grouping = args.summary_by.unwrap_or(SummaryGrouping::Language);
+    let snapshots = open_repository(paths).and_then(|(repository, workdir)| {
+        walk_history(
+            &repository,
+            revision,
+            sampling,
+            encoding,
+            grouping,
+            |relative| is_counted_below(&workdir.join(relative), paths, &exclude_options),
+        )
+    });
+    let snapshots = snapshots.unwrap_or_else(|err| {
+        eprintln!("Could not read the history. {}", err.message());
+        process::exit(1);
+    });
+    match args.output {
+        OutputFormat::Text => print_history_text(&snapshots),
+        OutputFormat::Json => print_history_json(&snapshots, sampling, grouping),
+        OutputFormat::Csv => print_history_csv(&snapshots, grouping, b','),
+        OutputFormat::Tsv => print_history_csv(&snapshots, grouping, b'\t'),
+        _ => {
+            eprintln!("History can only be reported as text, json, csv or tsv");
+            process::exit(1);
+        }
+    }
+}
+
+/// Opens the git repository containing the paths, along with its canonical working tree.
+/// Fails when the paths are not all in the same repository.
+fn open_repository(paths: &[PathBuf]) -> Result<(Repository, PathBuf), git2::Error> {
+    let open = |path: &Path| -> Result<(Repository, PathBuf), git2::Error> {
+        let repository = Repository::discover(path)?;
+        let workdir = repository
+            .workdir()
+            .and_then(|workdir| fs::canonicalize(workdir).ok())
+            .ok_or_else(|| git2::Error::from_str("the repository has no working tree"))?;
+        Ok((repository, workdir))
+    };
+    let (repository, workdir) = open(&paths[0])?;
+    for path in &paths[1..] {
+        if open(path)?.1 != workdir {
+            return Err(git2::Error::from_str(&format!(
+                "{} is not in the repository of {}",
+                path.display(),
+                paths[0].display()
+            )));
+        }
+    }
+    Ok((repository, workdir))
+}
+
+/// Whether a count of `paths` would count the file at `path`
+fn is_counted_below<'a>(
+    path: &Path,
+    paths: &'a [PathBuf],
+    exclude_options: &impl Fn(&'a Path) -> ExcludeOptions<'a>,
+) -> bool {
+    paths
+        .iter()
+        .find(|root| path.starts_with(root))
+        .is_some_and(|root| exclude_options(root).is_counted(path))
+}
+
+/// Stops the count at an unreadable entry with `--strict`, keeping what was already written
+fn stop_count(err: std::io::Error, printer: &dyn ResultPrinter) -> ! {
+    printer.flush();
+    stdout().flush().expect("Failed to write output");
+    eprintln!("Count failed. {err}");
+    process::exit(STRICT_EXIT_CODE);
+}
+
+fn load_language_definitions(path: &Path) -> Vec<Language> {
+    match load_definitions(path) {
+        Ok(languages) => languages,
+        Err(err) => {
+            eprintln!("Invalid language definitions. {err}");
+            process::exit(1);
+        }
+    }
 }
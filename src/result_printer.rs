@@ -1,14 +1,42 @@
+pub mod cloc_result_printer;
+pub mod csv_result_printer;
 pub mod debug_result_printer;
+pub mod json_result_printer;
 pub mod noop_result_printer;
 pub mod simple_result_printer;
+pub mod tokei_result_printer;
 pub mod verbose_result_printer;
 
 use crate::counter_walker::walk_path_result::WalkPathResult;
+use crate::language::Language;
 use crate::line_counter::{LineCount, LineCountFormat};
+use crate::summarizer::Summarizer;
+use clap::ValueEnum;
 use encoding_rs::Encoding;
+use std::ffi::OsString;
+use std::io::Error;
 use std::path::Path;
 use std::time::Duration;
 
+/// Format of the final report
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable text
+    Text,
+    /// A single JSON document, see the README for its schema
+    Json,
+    /// One comma separated row per file
+    Csv,
+    /// One tab separated row per file
+    Tsv,
+    /// cloc's YAML report
+    ClocYaml,
+    /// cloc's XML report
+    ClocXml,
+    /// tokei's JSON report
+    TokeiJson,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct FinalDisplayOptions {
     pub show_all: bool,
@@ -18,20 +46,17 @@ pub struct FinalDisplayOptions {
     pub line_count_format: LineCountFormat,
 }
 
+/// File or folder being reported. Names are kept as the operating system returned them,
+/// printers render them lossily unless their format can carry the raw bytes.
 #[derive(Debug)]
 pub struct PrinterEntry<'a> {
-    pub name: String,
+    pub name: OsString,
     pub path: &'a Path,
 }
 
 impl PrinterEntry<'_> {
     pub fn from_path(path: &Path) -> PrinterEntry<'_> {
-        let name = path
-            .file_name()
-            .unwrap()
-            .to_os_string()
-            .into_string()
-            .unwrap();
+        let name = path.file_name().unwrap_or(path.as_os_str()).to_os_string();
         PrinterEntry { name, path }
     }
 }
@@ -45,12 +70,14 @@ pub trait ResultPrinter {
     fn print_header(&self, path: &Path, num_entries: usize);
 
     fn print_folder(&self, entry: &PrinterEntry, num_entries: usize, depth: i32);
+    #[allow(clippy::too_many_arguments)]
     fn print_file(
         &self,
         entry: &PrinterEntry,
         lines: LineCount,
         process_time: i64,
         encoding: &'static Encoding,
+        language: Option<&'static Language>,
         depth: i32,
         confidence: f32,
     );
@@ -59,6 +86,7 @@ pub trait ResultPrinter {
         entry: &PrinterEntry,
         process_time: i64,
         encoding: &'static Encoding,
+        language: Option<&'static Language>,
         depth: i32,
         confidence: f32,
     );
@@ -67,9 +95,18 @@ pub trait ResultPrinter {
         entry: &PrinterEntry,
         process_time: i64,
         encoding: &'static Encoding,
+        language: Option<&'static Language>,
         depth: i32,
         confidence: f32,
     );
 
+    /// Reports an entry which could not be read, such as a folder without permission
+    fn print_walk_error(&self, entry: &PrinterEntry, error: &Error, depth: i32);
+
+    fn print_summary(&self, summarizer: &mut dyn Summarizer, total: LineCount);
+
+    /// Writes out the rows buffered so far, when the count is stopped before its result
+    fn flush(&self) {}
+
     fn requires_advanced_walker(&self) -> bool;
 }
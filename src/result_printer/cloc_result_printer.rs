@@ -0,0 +1,221 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Error;
+use std::path::Path;
+use std::time::Duration;
+
+use encoding_rs::Encoding;
+
+use crate::counter_walker::walk_path_result::WalkPathResult;
+use crate::language::Language;
+use crate::line_counter::LineCount;
+use crate::result_printer::{FinalDisplayOptions, PrinterEntry, ResultPrinter};
+use crate::summarizer::Summarizer;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClocFormat {
+    Yaml,
+    Xml,
+}
+
+/// Language names which cloc spells differently
+const CLOC_NAMES: &[(&str, &str)] = &[
+    ("Shell", "Bourne Shell"),
+    ("Makefile", "make"),
+    ("Vue", "Vuejs Component"),
+    ("Verilog", "Verilog-SystemVerilog"),
+];
+
+fn cloc_name(language: &Language) -> &'static str {
+    CLOC_NAMES
+        .iter()
+        .find(|(name, _)| *name == language.name)
+        .map_or(language.name, |(_, cloc)| cloc)
+}
+
+/// Reports totals per language in the YAML or XML layout of cloc's by-language report.
+/// Like cloc, files of an unknown language are left out.
+pub struct ClocResultPrinter {
+    format: ClocFormat,
+    languages: RefCell<HashMap<&'static str, (u64, LineCount)>>,
+}
+
+impl ClocResultPrinter {
+    pub fn new(format: ClocFormat) -> ClocResultPrinter {
+        ClocResultPrinter {
+            format,
+            languages: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn print_yaml(
+        &self,
+        rows: &[(&'static str, (u64, LineCount))],
+        sum: (u64, LineCount),
+        time: f64,
+    ) {
+        println!("---");
+        println!("# {}", env!("CARGO_PKG_NAME"));
+        println!("header :");
+        println!("  cloc_url           : {}", env!("CARGO_PKG_NAME"));
+        println!("  cloc_version       : {}", env!("CARGO_PKG_VERSION"));
+        println!("  elapsed_seconds    : {time}");
+        println!("  n_files            : {}", sum.0);
+        println!("  n_lines            : {}", sum.1.lines);
+        println!("  files_per_second   : {}", per_second(sum.0, time));
+        println!("  lines_per_second   : {}", per_second(sum.1.lines, time));
+        for (name, (files, count)) in rows {
+            println!("'{}' :", name.replace('\'', "''"));
+            println!("  nFiles: {files}");
+            println!("  blank: {}", count.blank_lines);
+            println!("  comment: {}", count.comment_lines);
+            println!("  code: {}", count.code_lines());
+        }
+        println!("SUM:");
+        println!("  blank: {}", sum.1.blank_lines);
+        println!("  comment: {}", sum.1.comment_lines);
+        println!("  code: {}", sum.1.code_lines());
+        println!("  nFiles: {}", sum.0);
+    }
+
+    fn print_xml(
+        &self,
+        rows: &[(&'static str, (u64, LineCount))],
+        sum: (u64, LineCount),
+        time: f64,
+    ) {
+        println!("<?xml version=\"1.0\" encoding=\"UTF-8\"?><results>");
+        println!("<header>");
+        println!("  <cloc_url>{}</cloc_url>", env!("CARGO_PKG_NAME"));
+        println!(
+            "  <cloc_version>{}</cloc_version>",
+            env!("CARGO_PKG_VERSION")
+        );
+        println!("  <elapsed_seconds>{time}</elapsed_seconds>");
+        println!("  <n_files>{}</n_files>", sum.0);
+        println!("  <n_lines>{}</n_lines>", sum.1.lines);
+        println!(
+            "  <files_per_second>{}</files_per_second>",
+            per_second(sum.0, time)
+        );
+        println!(
+            "  <lines_per_second>{}</lines_per_second>",
+            per_second(sum.1.lines, time)
+        );
+        println!("</header>");
+        println!("<languages>");
+        for (name, (files, count)) in rows {
+            println!(
+                "  <language name=\"{}\" files_count=\"{files}\" blank=\"{}\" comment=\"{}\" code=\"{}\" />",
+                escape_xml(name),
+                count.blank_lines,
+                count.comment_lines,
+                count.code_lines()
+            );
+        }
+        println!(
+            "  <total sum_files=\"{}\" blank=\"{}\" comment=\"{}\" code=\"{}\" />",
+            sum.0,
+            sum.1.blank_lines,
+            sum.1.comment_lines,
+            sum.1.code_lines()
+        );
+        println!("</languages>");
+        println!("</results>");
+    }
+}
+
+fn per_second(amount: u64, time: f64) -> f64 {
+    if time > 0.0 {
+        amount as f64 / time
+    } else {
+        0.0
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+impl ResultPrinter for ClocResultPrinter {
+    fn set_options(&mut self, _options: &FinalDisplayOptions) {}
+
+    fn print_result(&self, _total: WalkPathResult, time: &Duration) {
+        let mut rows: Vec<(&'static str, (u64, LineCount))> =
+            self.languages.take().into_iter().collect();
+        rows.sort_by_key(|row| (std::cmp::Reverse(row.1 .1.code_lines()), row.0));
+        let mut sum = (0u64, LineCount::new());
+        for (_, (files, count)) in &rows {
+            sum.0 += files;
+            sum.1 += *count;
+        }
+        match self.format {
+            ClocFormat::Yaml => self.print_yaml(&rows, sum, time.as_secs_f64()),
+            ClocFormat::Xml => self.print_xml(&rows, sum, time.as_secs_f64()),
+        }
+    }
+
+    fn print_subtotal(&self, _total: LineCount) {}
+
+    fn print_folder_total(&self, _total: LineCount, _depth: i32) {}
+
+    fn print_header(&self, _path: &Path, _num_entries: usize) {}
+
+    fn print_folder(&self, _entry: &PrinterEntry, _num_entries: usize, _depth: i32) {}
+
+    fn print_file(
+        &self,
+        _entry: &PrinterEntry,
+        lines: LineCount,
+        _process_time: i64,
+        _encoding: &'static Encoding,
+        language: Option<&'static Language>,
+        _depth: i32,
+        _confidence: f32,
+    ) {
+        if let Some(language) = language {
+            let mut languages = self.languages.borrow_mut();
+            let row = languages
+                .entry(cloc_name(language))
+                .or_insert((0, LineCount::new()));
+            row.0 += 1;
+            row.1 += lines;
+        }
+    }
+
+    fn print_empty_file(
+        &self,
+        _entry: &PrinterEntry,
+        _process_time: i64,
+        _encoding: &'static Encoding,
+        _language: Option<&'static Language>,
+        _depth: i32,
+        _confidence: f32,
+    ) {
+    }
+
+    fn print_error_file(
+        &self,
+        _entry: &PrinterEntry,
+        _process_time: i64,
+        _encoding: &'static Encoding,
+        _language: Option<&'static Language>,
+        _depth: i32,
+        _confidence: f32,
+    ) {
+    }
+
+    /// Unreadable entries have no place in cloc's per-language layout, so they are left out
+    fn print_walk_error(&self, _entry: &PrinterEntry, _error: &Error, _depth: i32) {}
+
+    /// Summaries have no place in cloc's layout, so they are left out
+    fn print_summary(&self, _summarizer: &mut dyn Summarizer, _total: LineCount) {}
+
+    fn requires_advanced_walker(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,148 @@
+use std::cell::RefCell;
+use std::io::Error;
+use std::io::{stdout, Stdout};
+use std::path::Path;
+use std::time::Duration;
+
+use encoding_rs::Encoding;
+
+use crate::counter_walker::walk_path_result::WalkPathResult;
+use crate::language::Language;
+use crate::line_counter::LineCount;
+use crate::result_printer::{FinalDisplayOptions, PrinterEntry, ResultPrinter};
+use crate::summarizer::Summarizer;
+
+/// Writes one delimited row per counted file, for spreadsheet analysis
+pub struct CsvResultPrinter {
+    writer: RefCell<csv::Writer<Stdout>>,
+}
+
+impl CsvResultPrinter {
+    pub fn new(delimiter: u8) -> CsvResultPrinter {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(delimiter)
+            .from_writer(stdout());
+        writer
+            .write_record([
+                "path", "language", "lines", "code", "comment", "blank", "bytes", "encoding",
+                "status",
+            ])
+            .expect("Failed to write output");
+        CsvResultPrinter {
+            writer: RefCell::new(writer),
+        }
+    }
+
+    fn write_row(
+        &self,
+        entry: &PrinterEntry,
+        lines: Option<LineCount>,
+        encoding: Option<&'static Encoding>,
+        language: Option<&'static Language>,
+        status: &str,
+    ) {
+        let counts = match lines {
+            Some(lines) => [
+                lines.lines,
+                lines.code_lines(),
+                lines.comment_lines,
+                lines.blank_lines,
+                lines.bytes,
+            ]
+            .map(|n| n.to_string()),
+            None => Default::default(),
+        };
+        let [lines, code, comment, blank, bytes] = counts;
+        self.writer
+            .borrow_mut()
+            .write_record([
+                entry.path.display().to_string(),
+                language.map_or("".to_string(), |l| l.name.to_string()),
+                lines,
+                code,
+                comment,
+                blank,
+                bytes,
+                encoding.map_or("".to_string(), |e| e.name().to_string()),
+                status.to_string(),
+            ])
+            .expect("Failed to write output");
+    }
+}
+
+impl ResultPrinter for CsvResultPrinter {
+    fn set_options(&mut self, _options: &FinalDisplayOptions) {}
+
+    fn print_result(&self, _total: WalkPathResult, _time: &Duration) {
+        self.flush();
+    }
+
+    fn print_subtotal(&self, _total: LineCount) {}
+
+    fn print_folder_total(&self, _total: LineCount, _depth: i32) {}
+
+    fn print_header(&self, _path: &Path, _num_entries: usize) {}
+
+    fn print_folder(&self, _entry: &PrinterEntry, _num_entries: usize, _depth: i32) {}
+
+    fn print_file(
+        &self,
+        entry: &PrinterEntry,
+        lines: LineCount,
+        _process_time: i64,
+        encoding: &'static Encoding,
+        language: Option<&'static Language>,
+        _depth: i32,
+        _confidence: f32,
+    ) {
+        self.write_row(entry, Some(lines), Some(encoding), language, "counted");
+    }
+
+    fn print_empty_file(
+        &self,
+        entry: &PrinterEntry,
+        _process_time: i64,
+        encoding: &'static Encoding,
+        language: Option<&'static Language>,
+        _depth: i32,
+        _confidence: f32,
+    ) {
+        self.write_row(
+            entry,
+            Some(LineCount::new()),
+            Some(encoding),
+            language,
+            "empty",
+        );
+    }
+
+    fn print_error_file(
+        &self,
+        entry: &PrinterEntry,
+        _process_time: i64,
+        encoding: &'static Encoding,
+        language: Option<&'static Language>,
+        _depth: i32,
+        _confidence: f32,
+    ) {
+        self.write_row(entry, None, Some(encoding), language, "error");
+    }
+
+    fn print_walk_error(&self, entry: &PrinterEntry, _error: &Error, _depth: i32) {
+        self.write_row(entry, None, None, None, "unreadable");
+    }
+
+    /// Summaries would break the table, so they are left out
+    fn print_summary(&self, _summarizer: &mut dyn Summarizer, _total: LineCount) {}
+
+    fn flush(&self) {
+        self.writer
+            .borrow_mut()
+            .flush()
+            .expect("Failed to write output");
+    }
+
+    fn requires_advanced_walker(&self) -> bool {
+        false
+    }
+}
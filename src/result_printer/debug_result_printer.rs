@@ -1,7 +1,10 @@
 use crate::counter_walker::walk_path_result::WalkPathResult;
+use crate::language::Language;
 use crate::line_counter::LineCount;
 use crate::result_printer::{FinalDisplayOptions, PrinterEntry, ResultPrinter};
+use crate::summarizer::Summarizer;
 use encoding_rs::Encoding;
+use std::io::Error;
 use std::path::Path;
 use std::time::Duration;
 
@@ -25,11 +28,14 @@ impl ResultPrinter for DebugResultPrinter {
     }
 
     fn print_header(&self, path: &Path, num_entries: usize) {
-        println!("{} :: {num_entries}", path.to_str().unwrap());
+        println!("{} :: {num_entries}", path.display());
     }
 
     fn print_folder(&self, entry: &PrinterEntry, num_entries: usize, depth: i32) {
-        println!("{depth} folder: {} :: {num_entries}", entry.name);
+        println!(
+            "{depth} folder: {} :: {num_entries}",
+            entry.name.to_string_lossy()
+        );
     }
 
     fn print_file(
@@ -38,12 +44,14 @@ impl ResultPrinter for DebugResultPrinter {
         lines: LineCount,
         _process_time: i64,
         encoding: &'static Encoding,
+        language: Option<&'static Language>,
         depth: i32,
         confidence: f32,
     ) {
         println!(
-            "{depth} file: {} {lines:?} {encoding:?}[{confidence}]",
-            entry.name
+            "{depth} file: {} {lines:?} {encoding:?} {:?}[{confidence}]",
+            entry.name.to_string_lossy(),
+            language.map(|l| l.name)
         );
     }
 
@@ -52,10 +60,15 @@ impl ResultPrinter for DebugResultPrinter {
         entry: &PrinterEntry,
         _process_time: i64,
         encoding: &'static Encoding,
+        language: Option<&'static Language>,
         depth: i32,
         confidence: f32,
     ) {
-        println!("{depth} empty: {} {encoding:?}[{confidence}]", entry.name);
+        println!(
+            "{depth} empty: {} {encoding:?} {:?}[{confidence}]",
+            entry.name.to_string_lossy(),
+            language.map(|l| l.name)
+        );
     }
 
     fn print_error_file(
@@ -63,10 +76,26 @@ impl ResultPrinter for DebugResultPrinter {
         entry: &PrinterEntry,
         _process_time: i64,
         encoding: &'static Encoding,
+        language: Option<&'static Language>,
         depth: i32,
         confidence: f32,
     ) {
-        println!("{depth} error: {} {encoding:?}[{confidence}]", entry.name);
+        println!(
+            "{depth} error: {} {encoding:?} {:?}[{confidence}]",
+            entry.name.to_string_lossy(),
+            language.map(|l| l.name)
+        );
+    }
+
+    fn print_walk_error(&self, entry: &PrinterEntry, error: &Error, depth: i32) {
+        println!(
+            "{depth} unreadable: {} {error}",
+            entry.name.to_string_lossy()
+        );
+    }
+
+    fn print_summary(&self, summarizer: &mut dyn Summarizer, total: LineCount) {
+        summarizer.print_summary(total);
     }
 
     fn requires_advanced_walker(&self) -> bool {
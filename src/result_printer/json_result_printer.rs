@@ -0,0 +1,240 @@
+use std::cell::RefCell;
+use std::ffi::OsStr;
+use std::io::Error;
+use std::path::Path;
+use std::time::Duration;
+
+use encoding_rs::Encoding;
+use serde_json::{json, Map, Value};
+
+use crate::counter_walker::walk_path_result::WalkPathResult;
+use crate::language::Language;
+use crate::line_counter::LineCount;
+use crate::result_printer::{FinalDisplayOptions, PrinterEntry, ResultPrinter};
+use crate::summarizer::Summarizer;
+
+/// Version of the JSON document layout, bumped on any incompatible change
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// Folder whose children are still being walked
+struct OpenFolder {
+    node: Map<String, Value>,
+    children: Vec<Value>,
+    total: LineCount,
+}
+
+/// Collects the whole walk and writes it as a single JSON document once the count is done
+pub struct JsonResultPrinter {
+    open_folders: RefCell<Vec<OpenFolder>>,
+    roots: RefCell<Vec<Value>>,
+    summary: RefCell<Value>,
+}
+
+impl JsonResultPrinter {
+    pub fn new() -> JsonResultPrinter {
+        JsonResultPrinter {
+            open_folders: RefCell::new(vec![]),
+            roots: RefCell::new(vec![]),
+            summary: RefCell::new(Value::Null),
+        }
+    }
+
+    fn open_folder(&self, node: Map<String, Value>) {
+        self.open_folders.borrow_mut().push(OpenFolder {
+            node,
+            children: vec![],
+            total: LineCount::new(),
+        });
+    }
+
+    fn close_folder(&self, total: Option<LineCount>) {
+        let folder = match self.open_folders.borrow_mut().pop() {
+            Some(folder) => folder,
+            None => return,
+        };
+        let total = total.unwrap_or(folder.total);
+        let mut node = folder.node;
+        node.insert("total".to_string(), total.as_json());
+        node.insert("children".to_string(), Value::Array(folder.children));
+        self.add_child(Value::Object(node), total);
+    }
+
+    fn add_child(&self, child: Value, lines: LineCount) {
+        match self.open_folders.borrow_mut().last_mut() {
+            Some(parent) => {
+                parent.children.push(child);
+                parent.total += lines;
+            }
+            None => self.roots.borrow_mut().push(child),
+        }
+    }
+
+    /// Starts the node of an entry with its type, name and path. Names which are not
+    /// valid UTF-8 are written lossily, with their raw bytes in `name_escaped` and
+    /// `path_escaped`.
+    fn entry_node(kind: &str, entry: &PrinterEntry) -> Map<String, Value> {
+        let mut node = Map::new();
+        node.insert("type".to_string(), json!(kind));
+        insert_os_str(&mut node, "name", &entry.name);
+        insert_os_str(&mut node, "path", entry.path.as_os_str());
+        node
+    }
+
+    fn add_file(
+        &self,
+        entry: &PrinterEntry,
+        status: &str,
+        lines: Option<LineCount>,
+        encoding: &'static Encoding,
+        language: Option<&'static Language>,
+        confidence: f32,
+    ) {
+        let mut file = Self::entry_node("file", entry);
+        file.insert("status".to_string(), json!(status));
+        file.insert("language".to_string(), json!(language.map(|l| l.name)));
+        file.insert("encoding".to_string(), json!(encoding.name()));
+        let confidence = (confidence != -1f32).then_some(confidence);
+        file.insert("confidence".to_string(), json!(confidence));
+        file.insert(
+            "count".to_string(),
+            json!(lines.as_ref().map(LineCount::as_json)),
+        );
+        self.add_child(Value::Object(file), lines.unwrap_or_default());
+    }
+}
+
+impl ResultPrinter for JsonResultPrinter {
+    fn set_options(&mut self, _options: &FinalDisplayOptions) {}
+
+    fn print_result(&self, total: WalkPathResult, time: &Duration) {
+        while !self.open_folders.borrow().is_empty() {
+            self.close_folder(None);
+        }
+        let mut document = json!({
+            "schema_version": JSON_SCHEMA_VERSION,
+            "roots": self.roots.take(),
+            "result": {
+                "line_count": total.line_count.as_json(),
+                "folder_count": total.folder_count,
+                "file_count": total.file_count,
+                "empty_file_count": total.empty_file_count,
+                "error_file_count": total.error_file_count,
+                "unreadable_count": total.unreadable_count,
+                "duration_secs": time.as_secs_f64(),
+            },
+        });
+        let summary = self.summary.take();
+        if !summary.is_null() {
+            document["summary"] = summary;
+        }
+        println!("{}", serde_json::to_string_pretty(&document).unwrap());
+    }
+
+    fn print_subtotal(&self, total: LineCount) {
+        self.close_folder(Some(total));
+    }
+
+    fn print_folder_total(&self, total: LineCount, _depth: i32) {
+        self.close_folder(Some(total));
+    }
+
+    fn print_header(&self, path: &Path, num_entries: usize) {
+        let mut node = Map::new();
+        node.insert("type".to_string(), json!("root"));
+        insert_os_str(&mut node, "path", path.as_os_str());
+        node.insert("entries".to_string(), json!(num_entries));
+        self.open_folder(node);
+    }
+
+    fn print_folder(&self, entry: &PrinterEntry, num_entries: usize, _depth: i32) {
+        let mut node = Self::entry_node("folder", entry);
+        node.insert("entries".to_string(), json!(num_entries));
+        self.open_folder(node);
+    }
+
+    fn print_file(
+        &self,
+        entry: &PrinterEntry,
+        lines: LineCount,
+        _process_time: i64,
+        encoding: &'static Encoding,
+        language: Option<&'static Language>,
+        _depth: i32,
+        confidence: f32,
+    ) {
+        self.add_file(
+            entry,
+            "counted",
+            Some(lines),
+            encoding,
+            language,
+            confidence,
+        );
+    }
+
+    fn print_empty_file(
+        &self,
+        entry: &PrinterEntry,
+        _process_time: i64,
+        encoding: &'static Encoding,
+        language: Option<&'static Language>,
+        _depth: i32,
+        confidence: f32,
+    ) {
+        self.add_file(
+            entry,
+            "empty",
+            Some(LineCount::new()),
+            encoding,
+            language,
+            confidence,
+        );
+    }
+
+    fn print_error_file(
+        &self,
+        entry: &PrinterEntry,
+        _process_time: i64,
+        encoding: &'static Encoding,
+        language: Option<&'static Language>,
+        _depth: i32,
+        confidence: f32,
+    ) {
+        self.add_file(entry, "error", None, encoding, language, confidence);
+    }
+
+    fn print_walk_error(&self, entry: &PrinterEntry, error: &Error, _depth: i32) {
+        let mut node = Self::entry_node("unreadable", entry);
+        node.insert("error".to_string(), json!(error.to_string()));
+        self.add_child(Value::Object(node), LineCount::new());
+    }
+
+    fn print_summary(&self, summarizer: &mut dyn Summarizer, total: LineCount) {
+        *self.summary.borrow_mut() = summarizer.summary_json(total);
+    }
+
+    fn requires_advanced_walker(&self) -> bool {
+        true
+    }
+}
+
+/// Inserts `value` under `key`, adding `<key>_escaped` when it is not valid UTF-8
+fn insert_os_str(node: &mut Map<String, Value>, key: &str, value: &OsStr) {
+    node.insert(key.to_string(), json!(value.to_string_lossy()));
+    if value.to_str().is_none() {
+        node.insert(format!("{key}_escaped"), json!(escape_bytes(value)));
+    }
+}
+
+/// Keeps valid UTF-8 as is and writes every other byte as `\xNN`, doubling backslashes
+/// so the escaped form can be decoded back to the original bytes
+fn escape_bytes(value: &OsStr) -> String {
+    let mut escaped = String::new();
+    for chunk in value.as_encoded_bytes().utf8_chunks() {
+        escaped.push_str(&chunk.valid().replace('\\', "\\\\"));
+        for byte in chunk.invalid() {
+            escaped.push_str(&format!("\\x{byte:02x}"));
+        }
+    }
+    escaped
+}
@@ -1,7 +1,10 @@
 use crate::counter_walker::walk_path_result::WalkPathResult;
+use crate::language::Language;
 use crate::line_counter::LineCount;
 use crate::result_printer::{FinalDisplayOptions, PrinterEntry, ResultPrinter};
+use crate::summarizer::Summarizer;
 use encoding_rs::Encoding;
+use std::io::Error;
 use std::path::Path;
 use std::time::Duration;
 
@@ -32,6 +35,7 @@ impl ResultPrinter for NoopResultPrinter {
         _lines: LineCount,
         _process_time: i64,
         _encoding: &'static Encoding,
+        _language: Option<&'static Language>,
         _depth: i32,
         _confidence: f32,
     ) {
@@ -42,6 +46,7 @@ impl ResultPrinter for NoopResultPrinter {
         _entry: &PrinterEntry,
         _process_time: i64,
         _encoding: &'static Encoding,
+        _language: Option<&'static Language>,
         _depth: i32,
         _confidence: f32,
     ) {
@@ -52,11 +57,18 @@ impl ResultPrinter for NoopResultPrinter {
         _entry: &PrinterEntry,
         _process_time: i64,
         _encoding: &'static Encoding,
+        _language: Option<&'static Language>,
         _depth: i32,
         _confidence: f32,
     ) {
     }
 
+    fn print_walk_error(&self, _entry: &PrinterEntry, _error: &Error, _depth: i32) {}
+
+    fn print_summary(&self, summarizer: &mut dyn Summarizer, total: LineCount) {
+        summarizer.print_summary(total);
+    }
+
     fn requires_advanced_walker(&self) -> bool {
         false
     }
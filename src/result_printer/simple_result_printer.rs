@@ -1,7 +1,10 @@
 use crate::counter_walker::walk_path_result::WalkPathResult;
+use crate::language::Language;
 use crate::line_counter::{LineCount, LineCountFormat};
 use crate::result_printer::{FinalDisplayOptions, PrinterEntry, ResultPrinter};
+use crate::summarizer::Summarizer;
 use encoding_rs::Encoding;
+use std::io::Error;
 use std::path::Path;
 use std::time::Duration;
 
@@ -29,12 +32,18 @@ impl ResultPrinter for SimpleResultPrinter {
     }
 
     fn print_result(&self, total: WalkPathResult, time: &Duration) {
+        let unreadable_str = if total.unreadable_count == 0 {
+            "".to_string()
+        } else {
+            format!(" {} unreadable", total.unreadable_count)
+        };
         println!(
-            "{} file{} {} empty {} invalid {} folder{} {:?}",
+            "{} file{} {} empty {} invalid{} {} folder{} {:?}",
             total.total_files(),
             if total.total_files() == 1 { "" } else { "s" },
             total.empty_file_count,
             total.error_file_count,
+            unreadable_str,
             total.folder_count,
             if total.folder_count == 1 { "" } else { "s" },
             time
@@ -67,14 +76,16 @@ impl ResultPrinter for SimpleResultPrinter {
         lines: LineCount,
         _process_time: i64,
         encoding: &'static Encoding,
+        language: Option<&'static Language>,
         _depth: i32,
         confidence: f32,
     ) {
         if self.options.very_verbose {
             println!(
-                "{} :: {} [{}{}]",
+                "{} :: {} [{}{}{}]",
                 entry.path.display(),
                 lines.as_fmt_string(&self.options.line_count_format),
+                language.map_or("".to_string(), |l| format!("{} ", l.name)),
                 encoding.name(),
                 if confidence == -1f32 {
                     "".to_string()
@@ -92,6 +103,7 @@ impl ResultPrinter for SimpleResultPrinter {
         entry: &PrinterEntry,
         _process_time: i64,
         _encoding: &'static Encoding,
+        _language: Option<&'static Language>,
         _depth: i32,
         _confidence: f32,
     ) {
@@ -105,6 +117,7 @@ impl ResultPrinter for SimpleResultPrinter {
         entry: &PrinterEntry,
         _process_time: i64,
         _encoding: &'static Encoding,
+        _language: Option<&'static Language>,
         _depth: i32,
         _confidence: f32,
     ) {
@@ -113,6 +126,14 @@ impl ResultPrinter for SimpleResultPrinter {
         }
     }
 
+    fn print_walk_error(&self, entry: &PrinterEntry, error: &Error, _depth: i32) {
+        println!("{} :: UNREADABLE {error}", entry.path.display())
+    }
+
+    fn print_summary(&self, summarizer: &mut dyn Summarizer, total: LineCount) {
+        summarizer.print_summary(total);
+    }
+
     fn requires_advanced_walker(&self) -> bool {
         false
     }
@@ -0,0 +1,161 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::Error;
+use std::path::Path;
+use std::time::Duration;
+
+use encoding_rs::Encoding;
+use serde_json::{json, Map, Value};
+
+use crate::counter_walker::walk_path_result::WalkPathResult;
+use crate::language::Language;
+use crate::line_counter::LineCount;
+use crate::result_printer::{FinalDisplayOptions, PrinterEntry, ResultPrinter};
+use crate::summarizer::Summarizer;
+
+/// Language names which tokei spells differently
+const TOKEI_NAMES: &[(&str, &str)] = &[
+    ("C++", "Cpp"),
+    ("C#", "CSharp"),
+    ("Objective-C", "ObjectiveC"),
+    ("Shell", "Sh"),
+    ("Jupyter Notebook", "Jupyter"),
+    ("Qt Linguist", "Xml"),
+];
+
+fn tokei_name(language: &Language) -> String {
+    TOKEI_NAMES
+        .iter()
+        .find(|(name, _)| *name == language.name)
+        .map_or_else(
+            || language.name.replace(|c: char| !c.is_alphanumeric(), ""),
+            |(_, tokei)| tokei.to_string(),
+        )
+}
+
+fn stats_json(count: &LineCount) -> Value {
+    json!({
+        "blanks": count.blank_lines,
+        "code": count.code_lines(),
+        "comments": count.comment_lines,
+        "blobs": {},
+    })
+}
+
+/// Reports per-language totals and per-file reports in the layout of `tokei --output json`.
+/// Like tokei, files of an unknown language are left out.
+pub struct TokeiResultPrinter {
+    languages: RefCell<BTreeMap<String, Vec<(String, LineCount)>>>,
+}
+
+impl TokeiResultPrinter {
+    pub fn new() -> TokeiResultPrinter {
+        TokeiResultPrinter {
+            languages: RefCell::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl ResultPrinter for TokeiResultPrinter {
+    fn set_options(&mut self, _options: &FinalDisplayOptions) {}
+
+    fn print_result(&self, _total: WalkPathResult, _time: &Duration) {
+        let mut document = Map::new();
+        let mut total = LineCount::new();
+        let mut total_children = Map::new();
+        for (name, files) in self.languages.take() {
+            let mut count = LineCount::new();
+            let reports: Vec<Value> = files
+                .iter()
+                .map(|(path, lines)| {
+                    count += *lines;
+                    json!({ "name": path, "stats": stats_json(lines) })
+                })
+                .collect();
+            total += count;
+            total_children.insert(name.clone(), Value::Array(reports.clone()));
+            document.insert(
+                name,
+                json!({
+                    "blanks": count.blank_lines,
+                    "code": count.code_lines(),
+                    "comments": count.comment_lines,
+                    "reports": reports,
+                    "children": {},
+                    "inaccurate": false,
+                }),
+            );
+        }
+        document.insert(
+            "Total".to_string(),
+            json!({
+                "blanks": total.blank_lines,
+                "code": total.code_lines(),
+                "comments": total.comment_lines,
+                "reports": [],
+                "children": total_children,
+                "inaccurate": false,
+            }),
+        );
+        println!("{}", Value::Object(document));
+    }
+
+    fn print_subtotal(&self, _total: LineCount) {}
+
+    fn print_folder_total(&self, _total: LineCount, _depth: i32) {}
+
+    fn print_header(&self, _path: &Path, _num_entries: usize) {}
+
+    fn print_folder(&self, _entry: &PrinterEntry, _num_entries: usize, _depth: i32) {}
+
+    fn print_file(
+        &self,
+        entry: &PrinterEntry,
+        lines: LineCount,
+        _process_time: i64,
+        _encoding: &'static Encoding,
+        language: Option<&'static Language>,
+        _depth: i32,
+        _confidence: f32,
+    ) {
+        if let Some(language) = language {
+            self.languages
+                .borrow_mut()
+                .entry(tokei_name(language))
+                .or_default()
+                .push((entry.path.display().to_string(), lines));
+        }
+    }
+
+    fn print_empty_file(
+        &self,
+        _entry: &PrinterEntry,
+        _process_time: i64,
+        _encoding: &'static Encoding,
+        _language: Option<&'static Language>,
+        _depth: i32,
+        _confidence: f32,
+    ) {
+    }
+
+    fn print_error_file(
+        &self,
+        _entry: &PrinterEntry,
+        _process_time: i64,
+        _encoding: &'static Encoding,
+        _language: Option<&'static Language>,
+        _depth: i32,
+        _confidence: f32,
+    ) {
+    }
+
+    /// Unreadable entries have no place in tokei's per-language layout, so they are left out
+    fn print_walk_error(&self, _entry: &PrinterEntry, _error: &Error, _depth: i32) {}
+
+    /// Summaries have no place in tokei's layout, so they are left out
+    fn print_summary(&self, _summarizer: &mut dyn Summarizer, _total: LineCount) {}
+
+    fn requires_advanced_walker(&self) -> bool {
+        false
+    }
+}
@@ -1,4 +1,5 @@
 use std::cmp::max;
+use std::io::Error;
 use std::path::Path;
 use std::time::Duration;
 
@@ -8,8 +9,10 @@ use encoding_rs::Encoding;
 use num_format::{Locale, ToFormattedString};
 
 use crate::counter_walker::walk_path_result::WalkPathResult;
+use crate::language::Language;
 use crate::line_counter::{LineCount, LineCountFormat};
 use crate::result_printer::{FinalDisplayOptions, PrinterEntry, ResultPrinter};
+use crate::summarizer::Summarizer;
 
 pub struct VerboseResultPrinter {
     options: FinalDisplayOptions,
@@ -50,12 +53,21 @@ impl ResultPrinter for VerboseResultPrinter {
             Red.normal()
         }
         .paint(format!("{} invalid", total.error_file_count));
+        let unreadable_str = if total.unreadable_count == 0 {
+            "".to_string()
+        } else {
+            format!(
+                " {}",
+                Red.paint(format!("{} unreadable", total.unreadable_count))
+            )
+        };
         println!(
-            "{} file{} {} {} {} folder{} {:?}",
+            "{} file{} {} {}{} {} folder{} {:?}",
             total.total_files(),
             if total.total_files() == 1 { "" } else { "s" },
             empty_file_str,
             error_file_str,
+            unreadable_str,
             total.folder_count,
             if total.folder_count == 1 { "" } else { "s" },
             time
@@ -92,7 +104,7 @@ impl ResultPrinter for VerboseResultPrinter {
         println!(
             "{}{} :: {}",
             pad_ended(depth, "├"),
-            Purple.paint(&entry.name),
+            Purple.paint(entry.name.to_string_lossy()),
             Yellow.paint(num_entries.to_formatted_string(&Locale::en_GB))
         )
     }
@@ -103,12 +115,14 @@ impl ResultPrinter for VerboseResultPrinter {
         lines: LineCount,
         _process_time: i64,
         encoding: &'static Encoding,
+        language: Option<&'static Language>,
         depth: i32,
         confidence: f32,
     ) {
         let verbose_info = if self.options.very_verbose {
             format!(
-                " [{}{}]",
+                " [{}{}{}]",
+                language.map_or("".to_string(), |l| format!("{} ", l.name)),
                 encoding.name(),
                 if confidence == -1f32 {
                     "".to_string()
@@ -122,7 +136,7 @@ impl ResultPrinter for VerboseResultPrinter {
         println!(
             "{}{} :: {}{}",
             pad_ended(depth, "├"),
-            Green.paint(&entry.name),
+            Green.paint(entry.name.to_string_lossy()),
             lines.as_fmt_string(&self.options.line_count_format),
             White.dimmed().paint(verbose_info)
         );
@@ -133,6 +147,7 @@ impl ResultPrinter for VerboseResultPrinter {
         entry: &PrinterEntry,
         _process_time: i64,
         _encoding: &'static Encoding,
+        _language: Option<&'static Language>,
         depth: i32,
         _confidence: f32,
     ) {
@@ -140,7 +155,7 @@ impl ResultPrinter for VerboseResultPrinter {
             println!(
                 "{}{} :: {}",
                 pad_ended(depth, "├"),
-                Green.paint(&entry.name),
+                Green.paint(entry.name.to_string_lossy()),
                 White.dimmed().paint("EMPTY")
             );
         }
@@ -151,6 +166,7 @@ impl ResultPrinter for VerboseResultPrinter {
         entry: &PrinterEntry,
         _process_time: i64,
         _encoding: &'static Encoding,
+        _language: Option<&'static Language>,
         depth: i32,
         _confidence: f32,
     ) {
@@ -158,12 +174,26 @@ impl ResultPrinter for VerboseResultPrinter {
             println!(
                 "{}{} :: {}",
                 pad_ended(depth, "├"),
-                Green.paint(&entry.name),
+                Green.paint(entry.name.to_string_lossy()),
                 Red.paint("ERROR")
             );
         }
     }
 
+    fn print_walk_error(&self, entry: &PrinterEntry, error: &Error, depth: i32) {
+        println!(
+            "{}{} :: {} {}",
+            pad_ended(depth, "├"),
+            Green.paint(entry.name.to_string_lossy()),
+            Red.paint("UNREADABLE"),
+            White.dimmed().paint(error.to_string())
+        );
+    }
+
+    fn print_summary(&self, summarizer: &mut dyn Summarizer, total: LineCount) {
+        summarizer.print_summary(total);
+    }
+
     fn requires_advanced_walker(&self) -> bool {
         true
     }
@@ -1,14 +1,29 @@
+pub mod blame_summarizer;
+pub mod composite_summarizer;
 pub mod default_summarizer;
 pub mod leaderboard_summarizer;
 pub mod noop_summarizer;
+pub mod owners_summarizer;
+pub mod repository_cache;
 
-use crate::line_counter::LineCount;
+use crate::language::Language;
+use crate::line_counter::{EmbeddedCount, LineCount};
+use serde_json::Value;
 use std::path::Path;
 
 pub trait Summarizer {
-    fn append_entry(&mut self, file_path: &Path, entry: LineCount);
+    fn append_entry(
+        &mut self,
+        file_path: &Path,
+        entry: LineCount,
+        language: Option<&'static Language>,
+        embedded: &[EmbeddedCount],
+    );
 
     fn set_limit(&mut self, limit: u32);
 
     fn print_summary(&mut self, total: LineCount);
+
+    /// The summary as a JSON value, or null when there is nothing to summarize
+    fn summary_json(&mut self, total: LineCount) -> Value;
 }
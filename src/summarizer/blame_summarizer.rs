@@ -0,0 +1,207 @@
+use ansi_term::Color::White;
+use encoding_rs::Encoding;
+use git2::BlameOptions;
+use num_format::{Locale, ToFormattedString};
+use prettytable::{format, row, Table};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::language::Language;
+use crate::line_counter::{classify_lines, EmbeddedCount, LineCount};
+use crate::summarizer::repository_cache::RepositoryCache;
+use crate::summarizer::Summarizer;
+
+/// An author as resolved through the repository's `.mailmap`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Author {
+    name: String,
+    email: String,
+}
+
+#[derive(Clone, Copy, Default)]
+struct AuthorRow {
+    count: LineCount,
+    files: u64,
+}
+
+/// Lines of a file last changed by the same author, `None` for lines which are not committed
+struct BlameRange {
+    author: Option<Author>,
+    start: usize,
+    lines: usize,
+}
+
+/// Summarizes line counts by the author of the last change to each line, using git blame
+pub struct BlameSummarizer {
+    encoding: Option<&'static Encoding>,
+    repositories: RepositoryCache<()>,
+    results: HashMap<Option<Author>, AuthorRow>,
+    limit: u32,
+}
+
+impl BlameSummarizer {
+    pub fn new(encoding: Option<&'static Encoding>) -> Self {
+        BlameSummarizer {
+            encoding,
+            repositories: RepositoryCache::new(),
+            results: HashMap::new(),
+            limit: 0,
+        }
+    }
+
+    /// Blames the content of the file as it is on disk against the commits of `HEAD`,
+    /// or `None` when the file is not tracked in a repository
+    fn blame(&mut self, file_path: &Path, content: &[u8]) -> Option<Vec<BlameRange>> {
+        let cached = self.repositories.get(file_path.parent()?, |_| ())?;
+        let relative = file_path.strip_prefix(&cached.workdir).ok()?;
+        let blame = cached
+            .repository
+            .blame_file(relative, Some(BlameOptions::new().use_mailmap(true)))
+            .ok()?;
+        let blame = blame.blame_buffer(content).ok()?;
+        let ranges = blame
+            .iter()
+            .map(|hunk| BlameRange {
+                // Lines changed in the working tree have no commit, nor a signature
+                author: (!hunk.final_commit_id().is_zero()).then(|| {
+                    let signature = hunk.final_signature();
+                    Author {
+                        name: String::from_utf8_lossy(signature.name_bytes()).into_owned(),
+                        email: String::from_utf8_lossy(signature.email_bytes()).into_owned(),
+                    }
+                }),
+                start: hunk.final_start_line().saturating_sub(1),
+                lines: hunk.lines_in_hunk(),
+            })
+            .collect();
+        Some(ranges)
+    }
+
+    /// Rows sorted by most lines first
+    fn sorted_rows(&self) -> Vec<(&Option<Author>, &AuthorRow)> {
+        let mut rows: Vec<_> = self.results.iter().collect();
+        rows.sort_by(|a, b| b.1.count.lines.cmp(&a.1.count.lines).then(a.0.cmp(b.0)));
+        rows
+    }
+
+    /// Number of rows shown given the limit
+    fn shown_rows(&self, rows: usize) -> usize {
+        if self.limit == 0 {
+            rows
+        } else {
+            rows.min(self.limit as usize)
+        }
+    }
+
+    fn total_lines(&self) -> u64 {
+        self.results.values().map(|row| row.count.lines).sum()
+    }
+}
+
+impl Summarizer for BlameSummarizer {
+    fn append_entry(
+        &mut self,
+        file_path: &Path,
+        entry: LineCount,
+        language: Option<&'static Language>,
+        _embedded: &[EmbeddedCount],
+    ) {
+        if entry.lines == 0 {
+            return;
+        }
+        let content = fs::read(file_path).unwrap_or_default();
+        let ranges = self.blame(file_path, &content).unwrap_or_else(|| {
+            vec![BlameRange {
+                author: None,
+                start: 0,
+                lines: entry.lines as usize,
+            }]
+        });
+        // Lines which cannot be classified, for example when the file changed since it was
+        // counted, are counted as code
+        let classified = classify_lines(&content, self.encoding, language).unwrap_or_default();
+        let line = |index: usize| {
+            classified.get(index).copied().unwrap_or(LineCount {
+                lines: 1,
+                ..LineCount::new()
+            })
+        };
+
+        let mut authors = HashSet::new();
+        for range in ranges {
+            let row = self.results.entry(range.author.clone()).or_default();
+            for index in range.start..range.start + range.lines {
+                row.count += line(index);
+            }
+            if authors.insert(range.author) {
+                row.files += 1;
+            }
+        }
+    }
+
+    fn set_limit(&mut self, limit: u32) {
+        self.limit = limit
+    }
+
+    fn print_summary(&mut self, _total: LineCount) {
+        let total = self.total_lines();
+        let rows = self.sorted_rows();
+
+        let mut table = Table::new();
+        table.set_titles(row![
+            "author", "% total", "lines", "code", "comment", "blank", "files"
+        ]);
+        for (author, row) in rows.iter().take(self.shown_rows(rows.len())) {
+            table.add_row(row![
+                match author {
+                    Some(author) => format!("{} <{}>", author.name, author.email),
+                    None => "Not committed yet".to_string(),
+                },
+                format!("{:.3}%", (row.count.lines as f64) / (total as f64) * 100f64),
+                row.count.lines.to_formatted_string(&Locale::en_GB),
+                row.count.code_lines().to_formatted_string(&Locale::en_GB),
+                row.count.comment_lines.to_formatted_string(&Locale::en_GB),
+                row.count.blank_lines.to_formatted_string(&Locale::en_GB),
+                row.files
+            ]);
+        }
+
+        println!(); // Pretty padding
+
+        table.set_format(*format::consts::FORMAT_CLEAN);
+        table.printstd();
+
+        if self.shown_rows(rows.len()) < rows.len() {
+            println!(
+                "{}",
+                White.dimmed().paint(format!(
+                    "And {} more...",
+                    rows.len() - self.shown_rows(rows.len())
+                ))
+            );
+        }
+
+        println!(); // Pretty padding
+    }
+
+    fn summary_json(&mut self, _total: LineCount) -> Value {
+        let total = self.total_lines();
+        let rows = self.sorted_rows();
+        json!({
+            "type": "authors",
+            "rows": rows
+                .iter()
+                .take(self.shown_rows(rows.len()))
+                .map(|(author, row)| json!({
+                    "name": author.as_ref().map(|a| &a.name),
+                    "email": author.as_ref().map(|a| &a.email),
+                    "percent_total": (row.count.lines as f64) / (total as f64) * 100f64,
+                    "count": row.count.as_json(),
+                    "files": row.files,
+                }))
+                .collect::<Vec<Value>>(),
+        })
+    }
+}
@@ -0,0 +1,55 @@
+use crate::language::Language;
+use crate::line_counter::{EmbeddedCount, LineCount};
+use crate::summarizer::Summarizer;
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Passes every entry to several summarizers, so that they all summarize the same walk,
+/// and reports their summaries one after the other
+pub struct CompositeSummarizer {
+    summarizers: Vec<Box<dyn Summarizer>>,
+}
+
+impl CompositeSummarizer {
+    pub fn new(summarizers: Vec<Box<dyn Summarizer>>) -> Self {
+        CompositeSummarizer { summarizers }
+    }
+}
+
+impl Summarizer for CompositeSummarizer {
+    fn append_entry(
+        &mut self,
+        file_path: &Path,
+        entry: LineCount,
+        language: Option<&'static Language>,
+        embedded: &[EmbeddedCount],
+    ) {
+        for summarizer in &mut self.summarizers {
+            summarizer.append_entry(file_path, entry, language, embedded);
+        }
+    }
+
+    fn set_limit(&mut self, limit: u32) {
+        for summarizer in &mut self.summarizers {
+            summarizer.set_limit(limit);
+        }
+    }
+
+    fn print_summary(&mut self, total: LineCount) {
+        for summarizer in &mut self.summarizers {
+            summarizer.print_summary(total);
+        }
+    }
+
+    fn summary_json(&mut self, total: LineCount) -> Value {
+        json!({
+            "type": "composite",
+            "summaries": self
+                .summarizers
+                .iter_mut()
+                .map(|summarizer| summarizer.summary_json(total))
+                .filter(|summary| !summary.is_null())
+                .collect::<Vec<Value>>(),
+        })
+    }
+}
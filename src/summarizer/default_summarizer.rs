@@ -2,98 +2,409 @@ use ansi_term::Color::White;
 use humansize::{format_size, WINDOWS};
 use num_format::{Locale, ToFormattedString};
 use prettytable::{format, row, Table};
+use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
 
-use crate::line_counter::LineCount;
+use crate::language::heuristics::is_ambiguous_extension;
+use crate::language::Language;
+use crate::line_counter::{EmbeddedCount, LineCount};
 use crate::summarizer::Summarizer;
 
-/// Summarizes line counts for files grouped by their file extension
+/// What the rows of the summary are keyed on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SummaryGrouping {
+    Extension,
+    Language,
+    /// Folder containing the file, at most this many levels below the counted root
+    Directory(usize),
+}
+
+impl SummaryGrouping {
+    /// Name of the grouping in reports, also naming the key column of delimited ones
+    pub fn name(&self) -> &'static str {
+        match self {
+            SummaryGrouping::Extension => "extension",
+            SummaryGrouping::Language => "language",
+            SummaryGrouping::Directory(_) => "directory",
+        }
+    }
+
+    /// Title of the key column of summary tables
+    pub fn title(&self) -> &'static str {
+        match self {
+            SummaryGrouping::Extension => "type",
+            _ => self.name(),
+        }
+    }
+}
+
+impl FromStr for SummaryGrouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "extension" => Ok(SummaryGrouping::Extension),
+            "language" => Ok(SummaryGrouping::Language),
+            "dir" => Ok(SummaryGrouping::Directory(1)),
+            _ => match s.strip_prefix("dir:").map(str::parse::<usize>) {
+                Some(Ok(depth)) if depth > 0 => Ok(SummaryGrouping::Directory(depth)),
+                Some(_) => Err(format!(
+                    "invalid depth in '{s}', expected a number of folders from 1"
+                )),
+                None => Err(format!(
+                    "unknown grouping '{s}', expected 'extension', 'language' or 'dir[:depth]'"
+                )),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct SummaryRow {
+    count: LineCount,
+    files: u64,
+    embedded: Vec<EmbeddedCount>,
+    /// Extensions making up a language row, or languages making up a directory row
+    members: HashMap<String, (LineCount, u64)>,
+}
+
+/// Summarizes line counts for files grouped by their file extension, language or directory
 pub struct DefaultSummarizer {
-    results: HashMap<String, (LineCount, u64)>,
+    results: HashMap<String, SummaryRow>,
     limit: u32,
+    grouping: SummaryGrouping,
+    expand: bool,
+    /// Counted roots which directories are relative to, file paths are used as they are without
+    roots: Vec<PathBuf>,
 }
 
 impl DefaultSummarizer {
-    pub fn new() -> Self {
+    pub fn new(grouping: SummaryGrouping, expand: bool) -> Self {
         DefaultSummarizer {
             results: HashMap::new(),
             limit: 0,
+            grouping,
+            expand,
+            roots: vec![],
         }
     }
-}
 
-impl Summarizer for DefaultSummarizer {
-    fn append_entry(&mut self, file_path: &Path, entry: LineCount) {
-        let filename = file_path.file_name().unwrap_or("".as_ref());
-        let extension = file_path
-            .extension()
-            .unwrap_or(filename)
-            .to_str()
-            .unwrap()
-            .to_string();
-
-        if let std::collections::hash_map::Entry::Vacant(e) = self.results.entry(extension.clone())
-        {
-            e.insert((entry, 1));
-        } else {
-            let a = self.results.get_mut(&extension).unwrap();
-            a.0 += entry;
-            a.1 += 1;
-        }
+    /// Sets the counted roots, so that directories are keyed on their path relative to them
+    pub fn set_roots(&mut self, roots: &[PathBuf]) {
+        self.roots = roots.to_vec();
     }
 
-    fn set_limit(&mut self, limit: u32) {
-        self.limit = limit
+    /// The folder containing the file, cut to `depth` levels below its root, such as `crates/core`
+    fn directory_key(&self, file_path: &Path, depth: usize) -> String {
+        let root = self
+            .roots
+            .iter()
+            .filter(|root| file_path.starts_with(root))
+            .max_by_key(|root| root.as_os_str().len());
+        let relative = root.map_or(file_path, |root| file_path.strip_prefix(root).unwrap());
+        let folders: Vec<String> = relative
+            .parent()
+            .unwrap_or(Path::new(""))
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .take(depth)
+            .collect();
+        // Directories of separate roots may share a relative path, so they are keyed on their
+        // full path when there are several
+        match root.filter(|_| self.roots.len() > 1) {
+            Some(root) if folders.is_empty() => root.display().to_string(),
+            Some(root) => root.join(folders.join("/")).display().to_string(),
+            None if folders.is_empty() => ".".to_string(),
+            None => folders.join("/"),
+        }
     }
 
-    fn print_summary(&mut self, total: LineCount) {
-        let mut entries: Vec<(String, (LineCount, u64))> =
-            self.results.clone().into_iter().collect();
-        entries.sort_by(|a, b| b.1 .0.lines.cmp(&a.1 .0.lines));
+    /// The summary table, embedded languages listed right below their row and members below
+    /// those, along with the number of rows before the limit
+    fn summary_table(&self, total: LineCount) -> (Table, usize) {
+        let mut entries: Vec<(String, SummaryRow)> = self.results.clone().into_iter().collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.1.count.lines));
 
         let mut table = Table::new();
-        table.set_titles(row!["type", "% total", "lines", "blank", "size", "entries"]);
+        table.set_titles(row![
+            self.grouping.title(),
+            "% total",
+            "lines",
+            "code",
+            "comment",
+            "blank",
+            "size",
+            "entries"
+        ]);
 
         let mut limit = 0u32;
         for entry in &entries {
-            let bytes_formatted = format_size(entry.1 .0.bytes, WINDOWS);
+            let bytes_formatted = format_size(entry.1.count.bytes, WINDOWS);
             table.add_row(row![
                 entry.0,
                 format!(
                     "{:.3}%",
-                    (entry.1 .0.lines as f64) / (total.lines as f64) * 100f64
-                ),
-                entry.1 .0.lines.to_formatted_string(&Locale::en_GB),
-                format!(
-                    "{} ({:.0}%)",
-                    entry.1 .0.blank_lines.to_formatted_string(&Locale::en_GB),
-                    (entry.1 .0.blank_lines as f64) / (entry.1 .0.lines as f64) * 100.0
+                    (entry.1.count.lines as f64) / (total.lines as f64) * 100f64
                 ),
+                entry.1.count.lines.to_formatted_string(&Locale::en_GB),
+                entry
+                    .1
+                    .count
+                    .code_lines()
+                    .to_formatted_string(&Locale::en_GB),
+                format_share(entry.1.count.comment_lines, entry.1.count.lines),
+                format_share(entry.1.count.blank_lines, entry.1.count.lines),
                 bytes_formatted,
-                entry.1 .1
+                entry.1.files
             ]);
+            let mut embedded = entry.1.embedded.clone();
+            embedded.sort_by_key(|e| std::cmp::Reverse(e.count.lines));
+            for sub in embedded {
+                table.add_row(row![
+                    format!("  of which {}", sub.language.name),
+                    "",
+                    sub.count.lines.to_formatted_string(&Locale::en_GB),
+                    sub.count.code_lines().to_formatted_string(&Locale::en_GB),
+                    format_share(sub.count.comment_lines, sub.count.lines),
+                    format_share(sub.count.blank_lines, sub.count.lines),
+                    "",
+                    ""
+                ]);
+            }
+            // A single extension is the language row itself, so it is not worth repeating,
+            // while the languages of a directory always are
+            let is_directory = matches!(self.grouping, SummaryGrouping::Directory(_));
+            if entry.1.members.len() > 1 || is_directory {
+                let mut members: Vec<(&String, &(LineCount, u64))> =
+                    entry.1.members.iter().collect();
+                members.sort_by_key(|m| std::cmp::Reverse(m.1 .0.lines));
+                for (member_key, member) in members {
+                    table.add_row(row![
+                        format!("  {member_key}"),
+                        "",
+                        member.0.lines.to_formatted_string(&Locale::en_GB),
+                        member.0.code_lines().to_formatted_string(&Locale::en_GB),
+                        format_share(member.0.comment_lines, member.0.lines),
+                        format_share(member.0.blank_lines, member.0.lines),
+                        format_size(member.0.bytes, WINDOWS),
+                        member.1
+                    ]);
+                }
+            }
             limit += 1;
             if limit == self.limit {
                 break;
             }
         }
+        (table, entries.len())
+    }
+
+    /// Line count and number of files of every row, unsorted
+    pub fn row_counts(&self) -> impl Iterator<Item = (&str, LineCount, u64)> {
+        self.results
+            .iter()
+            .map(|(key, row)| (key.as_str(), row.count, row.files))
+    }
+}
+
+impl Summarizer for DefaultSummarizer {
+    fn append_entry(
+        &mut self,
+        file_path: &Path,
+        entry: LineCount,
+        language: Option<&'static Language>,
+        embedded: &[EmbeddedCount],
+    ) {
+        let filename = file_path.file_name().unwrap_or("".as_ref());
+        let mut extension = file_path
+            .extension()
+            .unwrap_or(filename)
+            .to_string_lossy()
+            .into_owned();
+        let key = match (self.grouping, language) {
+            (SummaryGrouping::Language, Some(language)) => language.name.to_string(),
+            (SummaryGrouping::Directory(depth), _) => {
+                let key = self.directory_key(file_path, depth);
+                extension = language.map_or(extension, |language| language.name.to_string());
+                key
+            }
+            _ => {
+                if let Some(language) = language.filter(|_| is_ambiguous_extension(&extension)) {
+                    extension = format!("{extension} ({})", language.name);
+                }
+                extension.clone()
+            }
+        };
+
+        let row = self.results.entry(key).or_default();
+        row.count += entry;
+        row.files += 1;
+        for sub in embedded {
+            match row
+                .embedded
+                .iter_mut()
+                .find(|e| e.language.name == sub.language.name)
+            {
+                Some(existing) => existing.count += sub.count,
+                None => row.embedded.push(*sub),
+            }
+        }
+        if self.expand || matches!(self.grouping, SummaryGrouping::Directory(_)) {
+            let member = row
+                .members
+                .entry(extension)
+                .or_insert((LineCount::new(), 0));
+            member.0 += entry;
+            member.1 += 1;
+        }
+    }
+
+    fn set_limit(&mut self, limit: u32) {
+        self.limit = limit
+    }
+
+    fn print_summary(&mut self, total: LineCount) {
+        let (mut table, entries) = self.summary_table(total);
 
         println!(); // Pretty padding
 
         table.set_format(*format::consts::FORMAT_CLEAN);
         table.printstd();
 
-        if self.limit < entries.len() as u32 && self.limit != 0 {
+        if self.limit < entries as u32 && self.limit != 0 {
             println!(
                 "{}",
-                White.dimmed().paint(format!(
-                    "And {} more...",
-                    entries.len() - self.limit as usize
-                ))
+                White
+                    .dimmed()
+                    .paint(format!("And {} more...", entries - self.limit as usize))
             );
         }
 
         println!(); // Pretty padding
     }
+
+    fn summary_json(&mut self, total: LineCount) -> Value {
+        let mut entries: Vec<(String, SummaryRow)> = self.results.clone().into_iter().collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.1.count.lines));
+        if self.limit != 0 {
+            entries.truncate(self.limit as usize);
+        }
+        let member_name = match self.grouping {
+            SummaryGrouping::Directory(_) => "language",
+            _ => "extension",
+        };
+        json!({
+            "type": "summary",
+            "grouping": self.grouping.name(),
+            "rows": entries
+                .iter()
+                .map(|(key, row)| {
+                    let mut members: Vec<(&String, &(LineCount, u64))> =
+                        row.members.iter().collect();
+                    members.sort_by_key(|m| std::cmp::Reverse(m.1 .0.lines));
+                    json!({
+                        "key": key,
+                        "percent_total": (row.count.lines as f64) / (total.lines as f64) * 100f64,
+                        "count": row.count.as_json(),
+                        "files": row.files,
+                        "members": members
+                            .iter()
+                            .map(|(member_key, member)| json!({
+                                member_name: member_key,
+                                "count": member.0.as_json(),
+                                "files": member.1,
+                            }))
+                            .collect::<Vec<Value>>(),
+                        "embedded": row
+                            .embedded
+                            .iter()
+                            .map(|e| json!({
+                                "language": e.language.name,
+                                "count": e.count.as_json(),
+                            }))
+                            .collect::<Vec<Value>>(),
+                    })
+                })
+                .collect::<Vec<Value>>(),
+        })
+    }
+}
+
+fn format_share(lines: u64, total: u64) -> String {
+    format!(
+        "{} ({:.0}%)",
+        lines.to_formatted_string(&Locale::en_GB),
+        (lines as f64) / (total as f64) * 100.0
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::BUILTIN_LANGUAGES;
+
+    fn language(name: &str) -> &'static Language {
+        BUILTIN_LANGUAGES
+            .iter()
+            .find(|language| language.name == name)
+            .unwrap()
+    }
+
+    fn lines(lines: u64) -> LineCount {
+        LineCount {
+            lines,
+            ..LineCount::new()
+        }
+    }
+
+    #[test]
+    fn embedded_rows_follow_their_directory_row() {
+        let mut summarizer = DefaultSummarizer::new(SummaryGrouping::Directory(1), false);
+        summarizer.set_roots(&[PathBuf::from("/repo")]);
+        let code_block = EmbeddedCount {
+            language: language("Rust"),
+            count: lines(4),
+        };
+        summarizer.append_entry(
+            Path::new("/repo/docs/guide.md"),
+            lines(20),
+            Some(language("Markdown")),
+            &[code_block],
+        );
+        summarizer.append_entry(
+            Path::new("/repo/docs/example.rs"),
+            lines(10),
+            Some(language("Rust")),
+            &[],
+        );
+        summarizer.append_entry(
+            Path::new("/repo/build.rs"),
+            lines(5),
+            Some(language("Rust")),
+            &[],
+        );
+
+        let (table, entries) = summarizer.summary_table(lines(35));
+        let keys: Vec<String> = table
+            .row_iter()
+            .map(|row| row.get_cell(0).unwrap().get_content())
+            .collect();
+        assert_eq!(entries, 2);
+        assert_eq!(
+            keys,
+            [
+                "docs",
+                "  of which Rust",
+                "  Markdown",
+                "  Rust",
+                ".",
+                "  Rust"
+            ]
+        );
+    }
 }
@@ -1,38 +1,236 @@
-use crate::line_counter::LineCount;
+use crate::language::Language;
+use crate::line_counter::{EmbeddedCount, LineCount};
 use crate::summarizer::Summarizer;
-use prettytable::{format, row, Table};
-use std::path::Path;
+use ansi_term::Color::White;
+use clap::ValueEnum;
+use humansize::{format_size, WINDOWS};
+use num_format::{Locale, ToFormattedString};
+use prettytable::{format, row, Cell, Table};
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
 
+/// What the leaderboard ranks files by
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum LeaderboardSort {
+    Lines,
+    Code,
+    Comments,
+    Blank,
+    Bytes,
+    /// Share of blank lines
+    BlankRatio,
+    /// Share of comment lines
+    CommentRatio,
+}
+
+impl LeaderboardSort {
+    fn value(&self, count: &LineCount) -> f64 {
+        let ratio = |lines: u64| {
+            if count.lines == 0 {
+                0f64
+            } else {
+                lines as f64 / count.lines as f64
+            }
+        };
+        match self {
+            LeaderboardSort::Lines => count.lines as f64,
+            LeaderboardSort::Code => count.code_lines() as f64,
+            LeaderboardSort::Comments => count.comment_lines as f64,
+            LeaderboardSort::Blank => count.blank_lines as f64,
+            LeaderboardSort::Bytes => count.bytes as f64,
+            LeaderboardSort::BlankRatio => ratio(count.blank_lines),
+            LeaderboardSort::CommentRatio => ratio(count.comment_lines),
+        }
+    }
+
+    fn is_ratio(&self) -> bool {
+        matches!(
+            self,
+            LeaderboardSort::BlankRatio | LeaderboardSort::CommentRatio
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+struct LeaderboardEntry {
+    path: PathBuf,
+    language: Option<&'static Language>,
+    count: LineCount,
+}
+
+/// Ranks files by a line count, optionally within each language
 pub struct LeaderboardSummarizer {
-    entries: Vec<(String, LineCount)>,
+    entries: Vec<LeaderboardEntry>,
+    limit: u32,
+    sort: LeaderboardSort,
+    order: SortOrder,
+    by_language: bool,
+    /// Counted roots which paths are shown relative to, when there is a single one
+    roots: Vec<PathBuf>,
 }
 
 impl LeaderboardSummarizer {
-    pub(crate) fn new() -> Self {
-        LeaderboardSummarizer { entries: vec![] }
+    pub fn new(sort: LeaderboardSort, order: SortOrder, by_language: bool) -> Self {
+        LeaderboardSummarizer {
+            entries: vec![],
+            limit: 0,
+            sort,
+            order,
+            by_language,
+            roots: vec![],
+        }
+    }
+
+    /// Sets the counted roots, so that paths are shown relative to the root when there is one
+    pub fn set_roots(&mut self, roots: &[PathBuf]) {
+        self.roots = roots.to_vec();
+    }
+
+    fn sort(&mut self) {
+        let (sort, order) = (self.sort, self.order);
+        self.entries.sort_by(|a, b| {
+            let ordering = sort.value(&a.count).total_cmp(&sort.value(&b.count));
+            let ordering = match order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            };
+            ordering.then_with(|| a.path.cmp(&b.path))
+        });
+        if self.by_language {
+            // Stable, so files stay ranked within each language
+            self.entries
+                .sort_by(|a, b| compare_languages(a.language, b.language));
+        }
+    }
+
+    /// Entries shown, once sorted, along with their rank, within their language when grouped,
+    /// and the number of entries left out by the limit
+    fn ranked(&self) -> (Vec<(usize, &LeaderboardEntry)>, usize) {
+        let mut ranked = vec![];
+        let mut rank = 0;
+        let mut language = None;
+        for entry in &self.entries {
+            if self.by_language && entry.language.map(|l| l.name) != language {
+                language = entry.language.map(|l| l.name);
+                rank = 0;
+            }
+            rank += 1;
+            if self.limit == 0 || rank <= self.limit as usize {
+                ranked.push((rank, entry));
+            }
+        }
+        let hidden = self.entries.len() - ranked.len();
+        (ranked, hidden)
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        match self.roots.as_slice() {
+            [root] if path != root => path.strip_prefix(root).unwrap_or(path),
+            _ => path,
+        }
+        .to_string_lossy()
+        .to_string()
+    }
+}
+
+/// Orders languages by name, files of an unknown language last
+fn compare_languages(a: Option<&Language>, b: Option<&Language>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.name.cmp(b.name),
+        (a, b) => b.is_some().cmp(&a.is_some()),
     }
 }
 
 impl Summarizer for LeaderboardSummarizer {
-    fn append_entry(&mut self, file_path: &Path, entry: LineCount) {
-        self.entries
-            .push((file_path.to_string_lossy().to_string(), entry))
+    fn append_entry(
+        &mut self,
+        file_path: &Path,
+        entry: LineCount,
+        language: Option<&'static Language>,
+        _embedded: &[EmbeddedCount],
+    ) {
+        self.entries.push(LeaderboardEntry {
+            path: file_path.to_path_buf(),
+            language,
+            count: entry,
+        })
     }
 
-    fn set_limit(&mut self, _limit: u32) {}
+    fn set_limit(&mut self, limit: u32) {
+        self.limit = limit
+    }
 
     fn print_summary(&mut self, _total: LineCount) {
-        self.entries.sort_by_key(|e| e.1.lines);
-        self.entries.reverse();
+        self.sort();
+        let (sort, by_language) = (self.sort, self.by_language);
+        let (ranked, hidden) = self.ranked();
 
         let mut table = Table::new();
-        table.set_titles(row!["#", "path", "lines"]);
-        for entry in self.entries.iter().enumerate() {
-            table.add_row(row![entry.0 + 1, entry.1 .0, entry.1 .1]);
+        let mut titles = row!["#", "path", "lines", "code", "comment", "blank", "size"];
+        if by_language {
+            titles.insert_cell(2, Cell::new("language"));
+        }
+        if sort.is_ratio() {
+            titles.add_cell(Cell::new("ratio"));
+        }
+        table.set_titles(titles);
+        for (rank, entry) in &ranked {
+            let count = &entry.count;
+            let mut row = row![
+                rank,
+                self.display_path(&entry.path),
+                count.lines.to_formatted_string(&Locale::en_GB),
+                count.code_lines().to_formatted_string(&Locale::en_GB),
+                count.comment_lines.to_formatted_string(&Locale::en_GB),
+                count.blank_lines.to_formatted_string(&Locale::en_GB),
+                format_size(count.bytes, WINDOWS)
+            ];
+            if by_language {
+                row.insert_cell(2, Cell::new(entry.language.map_or("", |l| l.name)));
+            }
+            if sort.is_ratio() {
+                row.add_cell(Cell::new(&format!("{:.1}%", sort.value(count) * 100f64)));
+            }
+            table.add_row(row);
         }
 
         println!();
         table.set_format(*format::consts::FORMAT_CLEAN);
         table.printstd();
+
+        if hidden > 0 {
+            println!("{}", White.dimmed().paint(format!("And {hidden} more...")));
+        }
+    }
+
+    fn summary_json(&mut self, _total: LineCount) -> Value {
+        self.sort();
+        let (ranked, _) = self.ranked();
+        let rows: Vec<Value> = ranked
+            .iter()
+            .map(|(rank, entry)| {
+                let mut row = json!({
+                    "rank": rank,
+                    "path": self.display_path(&entry.path),
+                    "count": entry.count.as_json(),
+                });
+                if self.by_language {
+                    row["language"] = json!(entry.language.map(|l| l.name));
+                }
+                row
+            })
+            .collect();
+        json!({
+            "type": "leaderboard",
+            "sort": self.sort.to_possible_value().map(|value| value.get_name().to_string()),
+            "order": self.order.to_possible_value().map(|value| value.get_name().to_string()),
+            "rows": rows,
+        })
     }
 }
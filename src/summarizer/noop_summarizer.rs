@@ -1,5 +1,7 @@
-use crate::line_counter::LineCount;
+use crate::language::Language;
+use crate::line_counter::{EmbeddedCount, LineCount};
 use crate::summarizer::Summarizer;
+use serde_json::Value;
 use std::path::Path;
 
 pub struct NoopSummarizer {}
@@ -11,9 +13,20 @@ impl NoopSummarizer {
 }
 
 impl Summarizer for NoopSummarizer {
-    fn append_entry(&mut self, _file_path: &Path, _entry: LineCount) {}
+    fn append_entry(
+        &mut self,
+        _file_path: &Path,
+        _entry: LineCount,
+        _language: Option<&'static Language>,
+        _embedded: &[EmbeddedCount],
+    ) {
+    }
 
     fn set_limit(&mut self, _limit: u32) {}
 
     fn print_summary(&mut self, _final_count: LineCount) {}
+
+    fn summary_json(&mut self, _total: LineCount) -> Value {
+        Value::Null
+    }
 }
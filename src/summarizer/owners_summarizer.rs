@@ -0,0 +1,198 @@
+pub mod code_owners;
+
+use ansi_term::Color::White;
+use num_format::{Locale, ToFormattedString};
+use prettytable::{format, row, Table};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::language::Language;
+use crate::line_counter::{EmbeddedCount, LineCount};
+use crate::summarizer::owners_summarizer::code_owners::CodeOwners;
+use crate::summarizer::repository_cache::RepositoryCache;
+use crate::summarizer::Summarizer;
+
+/// Number of the largest unowned files listed below the summary
+const UNOWNED_FILES_LISTED: usize = 10;
+
+#[derive(Clone, Copy, Default)]
+struct OwnerRow {
+    count: LineCount,
+    files: u64,
+}
+
+/// Summarizes line counts by the owners the repository's `CODEOWNERS` file assigns to each
+/// file. Files with several owners are counted for each of them.
+pub struct OwnersSummarizer {
+    /// Repositories seen so far, along with their rules
+    repositories: RepositoryCache<Option<CodeOwners>>,
+    results: HashMap<String, OwnerRow>,
+    unowned: OwnerRow,
+    /// Unowned files, relative to their repository when they are in one
+    unowned_files: Vec<(PathBuf, LineCount)>,
+    limit: u32,
+}
+
+impl OwnersSummarizer {
+    pub fn new() -> Self {
+        OwnersSummarizer {
+            repositories: RepositoryCache::new(),
+            results: HashMap::new(),
+            unowned: OwnerRow::default(),
+            unowned_files: vec![],
+            limit: 0,
+        }
+    }
+
+    /// Owner rows sorted by most lines first
+    fn sorted_rows(&self) -> Vec<(&String, &OwnerRow)> {
+        let mut rows: Vec<_> = self.results.iter().collect();
+        rows.sort_by(|a, b| b.1.count.lines.cmp(&a.1.count.lines).then(a.0.cmp(b.0)));
+        if self.limit != 0 {
+            rows.truncate(self.limit as usize);
+        }
+        rows
+    }
+
+    /// The largest unowned files, most lines first
+    fn largest_unowned(&mut self) -> &[(PathBuf, LineCount)] {
+        self.unowned_files
+            .sort_by(|a, b| b.1.lines.cmp(&a.1.lines).then_with(|| a.0.cmp(&b.0)));
+        &self.unowned_files[..self.unowned_files.len().min(UNOWNED_FILES_LISTED)]
+    }
+}
+
+impl Summarizer for OwnersSummarizer {
+    fn append_entry(
+        &mut self,
+        file_path: &Path,
+        entry: LineCount,
+        _language: Option<&'static Language>,
+        _embedded: &[EmbeddedCount],
+    ) {
+        let repository = file_path
+            .parent()
+            .and_then(|folder| self.repositories.get(folder, CodeOwners::find));
+        let (relative, owners) = match repository {
+            Some(cached) => {
+                let relative = file_path.strip_prefix(&cached.workdir).unwrap_or(file_path);
+                let owners: Vec<String> = cached
+                    .data
+                    .as_ref()
+                    .map(|rules| rules.owners(relative))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect();
+                (relative.to_path_buf(), owners)
+            }
+            None => (file_path.to_path_buf(), vec![]),
+        };
+
+        if owners.is_empty() {
+            self.unowned.count += entry;
+            self.unowned.files += 1;
+            self.unowned_files.push((relative, entry));
+        }
+        for owner in owners {
+            let row = self.results.entry(owner).or_default();
+            row.count += entry;
+            row.files += 1;
+        }
+    }
+
+    fn set_limit(&mut self, limit: u32) {
+        self.limit = limit
+    }
+
+    fn print_summary(&mut self, total: LineCount) {
+        let share = |lines: u64| format!("{:.3}%", (lines as f64) / (total.lines as f64) * 100f64);
+
+        let mut table = Table::new();
+        table.set_titles(row![
+            "owner", "% total", "lines", "code", "comment", "blank", "files"
+        ]);
+        let unowned = self.unowned;
+        let owners = self.sorted_rows();
+        let hidden = self.results.len() - owners.len();
+        let rows = owners
+            .into_iter()
+            .map(|(owner, row)| (owner.as_str(), *row))
+            .chain((unowned.files > 0).then_some(("Unowned", unowned)));
+        for (owner, row) in rows {
+            table.add_row(row![
+                owner,
+                share(row.count.lines),
+                row.count.lines.to_formatted_string(&Locale::en_GB),
+                row.count.code_lines().to_formatted_string(&Locale::en_GB),
+                row.count.comment_lines.to_formatted_string(&Locale::en_GB),
+                row.count.blank_lines.to_formatted_string(&Locale::en_GB),
+                row.files
+            ]);
+        }
+
+        println!(); // Pretty padding
+
+        table.set_format(*format::consts::FORMAT_CLEAN);
+        table.printstd();
+
+        if hidden > 0 {
+            println!(
+                "{}",
+                White.dimmed().paint(format!("And {hidden} more owners..."))
+            );
+        }
+
+        let largest = self.largest_unowned();
+        if !largest.is_empty() {
+            let mut table = Table::new();
+            table.set_titles(row!["largest unowned files", "lines"]);
+            for (path, count) in largest {
+                table.add_row(row![
+                    path.display(),
+                    count.lines.to_formatted_string(&Locale::en_GB)
+                ]);
+            }
+            println!(); // Pretty padding
+            table.set_format(*format::consts::FORMAT_CLEAN);
+            table.printstd();
+        }
+
+        println!(); // Pretty padding
+    }
+
+    fn summary_json(&mut self, total: LineCount) -> Value {
+        let percent = |lines: u64| (lines as f64) / (total.lines as f64) * 100f64;
+        let rows: Vec<Value> = self
+            .sorted_rows()
+            .iter()
+            .map(|(owner, row)| {
+                json!({
+                    "owner": owner,
+                    "percent_total": percent(row.count.lines),
+                    "count": row.count.as_json(),
+                    "files": row.files,
+                })
+            })
+            .collect();
+        let unowned = self.unowned;
+        json!({
+            "type": "owners",
+            "rows": rows,
+            "unowned": {
+                "percent_total": percent(unowned.count.lines),
+                "count": unowned.count.as_json(),
+                "files": unowned.files,
+                "largest": self
+                    .largest_unowned()
+                    .iter()
+                    .map(|(path, count)| json!({
+                        "path": path.display().to_string(),
+                        "count": count.as_json(),
+                    }))
+                    .collect::<Vec<Value>>(),
+            },
+        })
+    }
+}
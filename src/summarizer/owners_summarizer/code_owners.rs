@@ -0,0 +1,247 @@
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::fs;
+use std::path::Path;
+
+/// Where GitHub and GitLab look for the `CODEOWNERS` file, relative to the repository root,
+/// the first one found being used
+pub const CODEOWNERS_LOCATIONS: [&str; 4] = [
+    ".github/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+];
+
+/// Rules of a section of the file, in the order they are written
+struct Section {
+    globs: GlobSet,
+    /// Index in `owners` of the rule each glob of `globs` belongs to
+    glob_rules: Vec<usize>,
+    owners: Vec<Vec<String>>,
+}
+
+/// The rules of a `CODEOWNERS` file, in GitHub or GitLab syntax. Within a section the last
+/// matching rule wins, and the owners of a file are those of every section. Files without
+/// GitLab `[Section]` headers have a single section.
+pub struct CodeOwners {
+    sections: Vec<Section>,
+}
+
+impl CodeOwners {
+    /// Reads the first `CODEOWNERS` file found below the repository root
+    pub fn find(root: &Path) -> Option<CodeOwners> {
+        CODEOWNERS_LOCATIONS
+            .iter()
+            .find_map(|location| fs::read_to_string(root.join(location)).ok())
+            .map(|content| CodeOwners::parse(&content))
+    }
+
+    /// Parses the rules, skipping lines whose pattern is not a valid glob
+    pub fn parse(content: &str) -> CodeOwners {
+        let mut sections = vec![];
+        let mut section = SectionBuilder::new(vec![]);
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(default_owners) = section_header(line) {
+                sections.extend(section.build());
+                section = SectionBuilder::new(default_owners);
+                continue;
+            }
+            let mut tokens = line.split_whitespace();
+            let Some(pattern) = tokens.next() else {
+                continue;
+            };
+            let owners: Vec<String> = tokens
+                .take_while(|token| !token.starts_with('#'))
+                .map(str::to_string)
+                .collect();
+            section.add(pattern, owners);
+        }
+        sections.extend(section.build());
+        CodeOwners { sections }
+    }
+
+    /// Owners of the file at `relative_path` from the repository root, empty when it is unowned
+    pub fn owners(&self, relative_path: &Path) -> Vec<&str> {
+        let mut owners: Vec<&str> = vec![];
+        for section in &self.sections {
+            let last_match = section
+                .globs
+                .matches(relative_path)
+                .into_iter()
+                .map(|glob| section.glob_rules[glob])
+                .max();
+            if let Some(rule) = last_match {
+                for owner in &section.owners[rule] {
+                    if !owners.contains(&owner.as_str()) {
+                        owners.push(owner);
+                    }
+                }
+            }
+        }
+        owners
+    }
+}
+
+struct SectionBuilder {
+    globs: GlobSetBuilder,
+    glob_rules: Vec<usize>,
+    owners: Vec<Vec<String>>,
+    /// Owners of the rules of a GitLab section which do not name any
+    default_owners: Vec<String>,
+}
+
+impl SectionBuilder {
+    fn new(default_owners: Vec<String>) -> Self {
+        SectionBuilder {
+            globs: GlobSetBuilder::new(),
+            glob_rules: vec![],
+            owners: vec![],
+            default_owners,
+        }
+    }
+
+    fn add(&mut self, pattern: &str, owners: Vec<String>) {
+        let Some(globs) = pattern_globs(pattern) else {
+            return;
+        };
+        let rule = self.owners.len();
+        for glob in globs {
+            self.globs.add(glob);
+            self.glob_rules.push(rule);
+        }
+        self.owners.push(if owners.is_empty() {
+            self.default_owners.clone()
+        } else {
+            owners
+        });
+    }
+
+    fn build(self) -> Option<Section> {
+        if self.owners.is_empty() {
+            return None;
+        }
+        Some(Section {
+            globs: self.globs.build().ok()?,
+            glob_rules: self.glob_rules,
+            owners: self.owners,
+        })
+    }
+}
+
+/// The default owners of a GitLab section header such as `^[Docs][2] @docs-team`,
+/// or `None` when the line is a rule
+fn section_header(line: &str) -> Option<Vec<String>> {
+    let header = line.strip_prefix('^').unwrap_or(line);
+    if !header.starts_with('[') {
+        return None;
+    }
+    let name_end = header.find(']')?;
+    let mut rest = &header[name_end + 1..];
+    // Number of approvals required, such as `[2]`
+    if let Some(approvals) = rest.strip_prefix('[') {
+        rest = &approvals[approvals.find(']')? + 1..];
+    }
+    Some(
+        rest.split_whitespace()
+            .take_while(|token| !token.starts_with('#'))
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Globs matching the files a pattern applies to. Patterns follow `.gitignore` rules: a leading
+/// or inner `/` anchors them to the repository root, otherwise they match at any depth. Folder
+/// patterns ending with `/`, and patterns naming a path without a wildcard in its last segment,
+/// also match everything below the folder, while `docs/*` only matches the direct children
+/// of `docs`.
+fn pattern_globs(pattern: &str) -> Option<Vec<globset::Glob>> {
+    // `\#` starts a pattern with a `#` rather than a comment
+    let pattern = pattern
+        .strip_prefix('\\')
+        .filter(|p| p.starts_with('#'))
+        .unwrap_or(pattern);
+    let folder_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let base = if trimmed.is_empty() || trimmed == "*" {
+        "**".to_string()
+    } else if trimmed.contains('/') {
+        trimmed.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{trimmed}")
+    };
+    let contents = format!("{base}/**");
+    let last_segment = base.rsplit('/').next().unwrap_or_default();
+    let has_wildcard = last_segment.contains(['*', '?', '[']);
+    let patterns = if base == "**" {
+        vec![base.as_str()]
+    } else if folder_only {
+        vec![contents.as_str()]
+    } else if has_wildcard {
+        vec![base.as_str()]
+    } else {
+        vec![base.as_str(), contents.as_str()]
+    };
+    patterns
+        .into_iter()
+        .map(|pattern| {
+            GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owners<'a>(rules: &'a CodeOwners, path: &str) -> Vec<&'a str> {
+        rules.owners(Path::new(path))
+    }
+
+    #[test]
+    fn wildcard_patterns_only_match_direct_children() {
+        let rules = CodeOwners::parse("docs/*  @docs\n");
+        assert_eq!(owners(&rules, "docs/index.md"), ["@docs"]);
+        assert!(owners(&rules, "docs/build-app/troubleshooting.md").is_empty());
+    }
+
+    #[test]
+    fn folder_patterns_match_everything_below() {
+        let rules = CodeOwners::parse("/build/logs/ @ops\napps @apps\n");
+        assert_eq!(owners(&rules, "build/logs/deep/a.log"), ["@ops"]);
+        assert!(owners(&rules, "src/build/logs/a.log").is_empty());
+        assert_eq!(owners(&rules, "src/apps/web/main.js"), ["@apps"]);
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let rules = CodeOwners::parse(
+            "# Default owners\n*  @org/all\n*.js  @web @octocat  # front end\n/src/ @src\n",
+        );
+        assert_eq!(owners(&rules, "README.md"), ["@org/all"]);
+        assert_eq!(owners(&rules, "lib/app.js"), ["@web", "@octocat"]);
+        assert_eq!(owners(&rules, "src/app.js"), ["@src"]);
+    }
+
+    #[test]
+    fn rules_without_owners_leave_files_unowned() {
+        let rules = CodeOwners::parse("*  @org/all\n/vendor/\n");
+        assert_eq!(owners(&rules, "src/main.rs"), ["@org/all"]);
+        assert!(owners(&rules, "vendor/lib/lib.rs").is_empty());
+    }
+
+    #[test]
+    fn gitlab_sections_each_pick_a_rule() {
+        let rules = CodeOwners::parse(
+            "*.rs @rust\n[Documentation][2] @docs\ndocs/\n*.md @writers\n^[Optional]\n*.rs @reviewers\n",
+        );
+        assert_eq!(owners(&rules, "src/main.rs"), ["@rust", "@reviewers"]);
+        assert_eq!(owners(&rules, "docs/guide.txt"), ["@docs"]);
+        assert_eq!(owners(&rules, "docs/guide.md"), ["@writers"]);
+    }
+}
@@ -0,0 +1,58 @@
+use git2::Repository;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A repository found by a [RepositoryCache], with the data loaded for it
+pub struct CachedRepository<T> {
+    /// Canonical working tree
+    pub workdir: PathBuf,
+    pub repository: Repository,
+    pub data: T,
+}
+
+/// The git repository containing each folder, discovered once per folder and opened once
+/// per repository, for summarizers which look up the repository of every file
+pub struct RepositoryCache<T> {
+    repositories: Vec<CachedRepository<T>>,
+    /// Index in `repositories` of the repository containing each folder seen so far
+    folders: HashMap<PathBuf, Option<usize>>,
+}
+
+impl<T> RepositoryCache<T> {
+    pub fn new() -> Self {
+        RepositoryCache {
+            repositories: vec![],
+            folders: HashMap::new(),
+        }
+    }
+
+    /// The repository containing `folder`, or `None` when it is not in a repository with a
+    /// working tree. `load` is called with the working tree the first time a repository is
+    /// found.
+    pub fn get(
+        &mut self,
+        folder: &Path,
+        load: impl FnOnce(&Path) -> T,
+    ) -> Option<&CachedRepository<T>> {
+        if !self.folders.contains_key(folder) {
+            let index = Repository::discover(folder).ok().and_then(|repository| {
+                let workdir = fs::canonicalize(repository.workdir()?).ok()?;
+                match self.repositories.iter().position(|r| r.workdir == workdir) {
+                    Some(index) => Some(index),
+                    None => {
+                        let data = load(&workdir);
+                        self.repositories.push(CachedRepository {
+                            workdir,
+                            repository,
+                            data,
+                        });
+                        Some(self.repositories.len() - 1)
+                    }
+                }
+            });
+            self.folders.insert(folder.to_path_buf(), index);
+        }
+        self.folders[folder].map(|index| &self.repositories[index])
+    }
+}